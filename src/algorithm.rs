@@ -0,0 +1,422 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use notation::{from_wca, WcaCorner, WcaMove};
+use rotation::Frame;
+use skewb::{Move, NormalizedSkewb};
+
+// The most moves an algorithm may expand to, so that a few characters like (R U)4000000000 can't
+// ask for more memory than there is.
+pub const MAX_MOVES: usize = 100_000;
+
+// An algorithm as it is written down, keeping its structure so it can be printed the same way.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Move(WcaMove),
+    Sequence(Vec<Algorithm>),
+    // Written (R U)3, or R3 for a single move.
+    Repeat(Box<Algorithm>, usize),
+    // Written (R U)'.
+    Inverse(Box<Algorithm>),
+    // [A, B], which is A B A' B'.
+    Commutator(Box<Algorithm>, Box<Algorithm>),
+    // [A: B], which is A B A'.
+    Conjugate(Box<Algorithm>, Box<Algorithm>),
+}
+
+impl Algorithm {
+    pub fn parse(text: &str) -> Result<Algorithm, ParseError> {
+        let mut parser = Parser { text, position: 0 };
+        let algorithm = parser.sequence()?;
+        match parser.peek() {
+            None => Ok(algorithm),
+            Some(c) => Err(parser.error(&format!("unexpected {}", c), 1)),
+        }
+    }
+
+    pub fn inverse(&self) -> Algorithm {
+        match *self {
+            Algorithm::Move(move_) => Algorithm::Move(move_.inverse()),
+            Algorithm::Sequence(ref items) => {
+                Algorithm::Sequence(items.iter().rev().map(Algorithm::inverse).collect())
+            }
+            Algorithm::Repeat(ref a, n) => Algorithm::Repeat(Box::new(a.inverse()), n),
+            Algorithm::Inverse(ref a) => (**a).clone(),
+            Algorithm::Commutator(ref a, ref b) => Algorithm::Commutator(b.clone(), a.clone()),
+            Algorithm::Conjugate(ref a, ref b) => {
+                Algorithm::Conjugate(a.clone(), Box::new(b.inverse()))
+            }
+        }
+    }
+
+    // Every move, with the structure expanded away.
+    pub fn wca_moves(&self) -> Vec<WcaMove> {
+        let mut moves = vec![];
+        self.expand_into(&mut moves);
+        moves
+    }
+
+    // How many moves wca_moves gives, or usize::MAX if that many couldn't be counted.
+    fn expanded_len(&self) -> usize {
+        match *self {
+            Algorithm::Move(_) => 1,
+            Algorithm::Sequence(ref items) => items
+                .iter()
+                .fold(0, |sum, item| sum.saturating_add(item.expanded_len())),
+            Algorithm::Repeat(ref a, n) => a.expanded_len().saturating_mul(n),
+            Algorithm::Inverse(ref a) => a.expanded_len(),
+            Algorithm::Commutator(ref a, ref b) => a
+                .expanded_len()
+                .saturating_add(b.expanded_len())
+                .saturating_mul(2),
+            Algorithm::Conjugate(ref a, ref b) => a
+                .expanded_len()
+                .saturating_mul(2)
+                .saturating_add(b.expanded_len()),
+        }
+    }
+
+    fn expand_into(&self, moves: &mut Vec<WcaMove>) {
+        match *self {
+            Algorithm::Move(move_) => moves.push(move_),
+            Algorithm::Sequence(ref items) => {
+                for item in items.iter() {
+                    item.expand_into(moves);
+                }
+            }
+            Algorithm::Repeat(ref a, n) => {
+                for _ in 0..n {
+                    a.expand_into(moves);
+                }
+            }
+            Algorithm::Inverse(ref a) => a.inverse().expand_into(moves),
+            Algorithm::Commutator(ref a, ref b) => {
+                a.expand_into(moves);
+                b.expand_into(moves);
+                a.inverse().expand_into(moves);
+                b.inverse().expand_into(moves);
+            }
+            Algorithm::Conjugate(ref a, ref b) => {
+                a.expand_into(moves);
+                b.expand_into(moves);
+                a.inverse().expand_into(moves);
+            }
+        }
+    }
+
+    // The fixed-corner moves that do the same, and where they leave the corners of the normalized
+    // puzzle on the puzzle being held, as from_wca.
+    pub fn moves(&self) -> (Vec<Move>, Frame) { from_wca(&self.wca_moves()) }
+
+    // The state the algorithm leaves a puzzle in, normalized, if it starts in the start state
+    // held in the canonical position.
+    pub fn evaluate(&self, start: &NormalizedSkewb) -> NormalizedSkewb {
+        start.applied(&self.moves().0)
+    }
+
+    fn fmt_item(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Algorithm::Sequence(_) => write!(f, "({})", self),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Algorithm::Move(move_) => write!(f, "{}", move_),
+            Algorithm::Sequence(ref items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    item.fmt_item(f)?;
+                }
+                Ok(())
+            }
+            Algorithm::Repeat(ref a, n) => {
+                a.fmt_item(f)?;
+                write!(f, "{}", n)
+            }
+            Algorithm::Inverse(ref a) => {
+                a.fmt_item(f)?;
+                write!(f, "'")
+            }
+            Algorithm::Commutator(ref a, ref b) => write!(f, "[{}, {}]", a, b),
+            Algorithm::Conjugate(ref a, ref b) => write!(f, "[{}: {}]", a, b),
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Algorithm, ParseError> { Algorithm::parse(text) }
+}
+
+// What is wrong with an algorithm, and the bytes of the text where it went wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.text[self.position..].chars().next() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.text[self.position..].chars().next()
+    }
+
+    // The character at the position, which must not be whitespace.
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.text[self.position..].chars().next()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    // An error about the characters from the position on.
+    fn error(&self, message: &str, chars: usize) -> ParseError {
+        let end = self.text[self.position..]
+            .char_indices()
+            .nth(chars)
+            .map_or(self.text.len(), |(i, _)| self.position + i);
+        ParseError {
+            message: message.to_string(),
+            span: self.position..end,
+        }
+    }
+
+    // An error about what was parsed from start up to the position if it expands to too many
+    // moves.
+    fn check_length(&self, algorithm: &Algorithm, start: usize) -> Result<(), ParseError> {
+        if algorithm.expanded_len() > MAX_MOVES {
+            return Err(ParseError {
+                message: format!("expands to more than {} moves", MAX_MOVES),
+                span: start..self.position,
+            });
+        }
+        Ok(())
+    }
+
+    // Items up to the end of the text or a closing bracket, separator or parenthesis.
+    fn sequence(&mut self) -> Result<Algorithm, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let mut items = vec![];
+        while let Some(c) = self.peek() {
+            if c == ')' || c == ']' || c == ',' || c == ':' {
+                break;
+            }
+            items.push(self.item()?);
+        }
+        let sequence = Algorithm::Sequence(items);
+        self.check_length(&sequence, start)?;
+        Ok(sequence)
+    }
+
+    fn item(&mut self) -> Result<Algorithm, ParseError> {
+        let start = self.position;
+        let mut item = match self.next_char() {
+            Some('(') => {
+                let inner = self.sequence()?;
+                self.close(start, ')')?;
+                inner
+            }
+            Some('[') => {
+                let a = Box::new(self.sequence()?);
+                let separator = self.peek();
+                match separator {
+                    Some(',') | Some(':') => self.position += 1,
+                    None => return Err(self.unclosed(start)),
+                    Some(c) => {
+                        return Err(self.error(&format!("expected , or : but found {}", c), 1))
+                    }
+                }
+                let b = Box::new(self.sequence()?);
+                self.close(start, ']')?;
+                let item = if separator == Some(',') {
+                    Algorithm::Commutator(a, b)
+                } else {
+                    Algorithm::Conjugate(a, b)
+                };
+                self.check_length(&item, start)?;
+                item
+            }
+            Some(c) => {
+                let corner = match c {
+                    'R' => WcaCorner::R,
+                    'U' => WcaCorner::U,
+                    'L' => WcaCorner::L,
+                    'B' => WcaCorner::B,
+                    _ => {
+                        self.position = start;
+                        return Err(self.error(&format!("{} is not a WCA move", c), 1));
+                    }
+                };
+                let prime = self.text[self.position..].starts_with('\'');
+                if prime {
+                    self.position += 1;
+                }
+                Algorithm::Move(WcaMove::new(corner, prime))
+            }
+            None => unreachable!("items start at a character"),
+        };
+
+        // Repetitions and inverses bind to what comes right before them, with no space between.
+        loop {
+            let rest = &self.text[self.position..];
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits > 0 {
+                let count = rest[..digits]
+                    .parse()
+                    .map_err(|_| self.error("too many repetitions", digits))?;
+                item = Algorithm::Repeat(Box::new(item), count);
+                self.position += digits;
+                self.check_length(&item, start)?;
+            } else if rest.starts_with('\'') {
+                item = Algorithm::Inverse(Box::new(item));
+                self.position += 1;
+            } else {
+                return Ok(item);
+            }
+        }
+    }
+
+    fn unclosed(&self, start: usize) -> ParseError {
+        ParseError {
+            message: format!("{} is never closed", &self.text[start..start + 1]),
+            span: start..start + 1,
+        }
+    }
+
+    // Expects the bracket closing the one opened at start.
+    fn close(&mut self, start: usize, bracket: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == bracket => {
+                self.position += 1;
+                Ok(())
+            }
+            None => Err(self.unclosed(start)),
+            Some(',') | Some(':') if bracket == ']' => {
+                Err(self.error("a commutator or conjugate has only two parts", 1))
+            }
+            Some(c) => Err(self.error(&format!("expected {} but found {}", bracket, c), 1)),
+        }
+    }
+}
+
+#[test]
+fn parse_and_print() {
+    for &text in [
+        "R U' L B",
+        "(R U)3",
+        "[R, U']",
+        "[R: U L]",
+        "[R: [U, L']] (B R)2'",
+        "[(R U)2: R'3]",
+        "",
+    ]
+    .iter()
+    {
+        let algorithm: Algorithm = text.parse().unwrap();
+        assert_eq!(text, algorithm.to_string());
+    }
+    assert_eq!("R U", Algorithm::parse("  R   U ").unwrap().to_string());
+}
+
+#[test]
+fn expansion() {
+    use notation::parse_wca;
+
+    let expand = |text| Algorithm::parse(text).unwrap().wca_moves();
+    assert_eq!(parse_wca("R U R U R U"), Ok(expand("(R U)3")));
+    assert_eq!(parse_wca("R U' R' U"), Ok(expand("[R, U']")));
+    assert_eq!(parse_wca("R U L R'"), Ok(expand("[R: U L]")));
+    assert_eq!(parse_wca("U' R'"), Ok(expand("(R U)'")));
+    assert_eq!(parse_wca("R L R' U R L' R' U'"), Ok(expand("[[R: L], U]")));
+    assert_eq!(parse_wca("U' R' U' R' U' R'"), Ok(expand("(R U)3'")));
+    assert_eq!(
+        expand("[R: U L]'"),
+        Algorithm::parse("[R: U L]").unwrap().inverse().wca_moves()
+    );
+    assert_eq!(
+        expand("[U, R]"),
+        Algorithm::parse("[R, U]").unwrap().inverse().wca_moves()
+    );
+}
+
+#[test]
+fn evaluation() {
+    use skewb::Direction;
+
+    let undone = Algorithm::parse("R U L B (R U L B)'").unwrap();
+    assert!(undone.evaluate(&NormalizedSkewb::new()).is_solved());
+
+    let (moves, frame) = Algorithm::parse("R U").unwrap().moves();
+    assert!(frame.is_identity());
+    assert_eq!(
+        vec![
+            Move::new((1, 1, 0), Direction::LR),
+            Move::new((0, 0, 0), Direction::LR),
+        ],
+        moves
+    );
+
+    let (moves, _) = Algorithm::parse("[B, R]").unwrap().moves();
+    let expected = NormalizedSkewb::after(&moves);
+    assert_eq!(
+        expected,
+        Algorithm::parse("[B, R]")
+            .unwrap()
+            .evaluate(&NormalizedSkewb::new())
+    );
+}
+
+#[test]
+fn errors_point_at_the_problem() {
+    let span = |text: &str| Algorithm::parse(text).unwrap_err().span;
+
+    assert_eq!(3..4, span("R (F U)"));
+    assert_eq!(2..3, span("R (U L"));
+    assert_eq!(3..4, span("R U) L"));
+    assert_eq!(1..2, span("R, U"));
+    assert_eq!(4..5, span("[R U]"));
+    assert_eq!(0..1, span("[R, U"));
+    assert_eq!(5..6, span("[R, U: L]"));
+    assert_eq!(3..23, span("(R)99999999999999999999"));
+    assert_eq!(0..15, span("(R U)4000000000"));
+    assert_eq!(2..19, span("R [[R, U]2000, L]40"));
+    assert_eq!(3..14, span("R [[R, U]30000, L]"));
+    assert_eq!(0..28, span("(R)60000 U (R)30000 (U)20000"));
+    assert!(Algorithm::parse(&format!("(R){}", MAX_MOVES)).is_ok());
+    assert_eq!(
+        "x is not a WCA move at 0..1",
+        Algorithm::parse("x").unwrap_err().to_string()
+    );
+}
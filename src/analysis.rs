@@ -0,0 +1,353 @@
+use std::fmt;
+
+use skewb::{
+    corner_name, Center, Corner, Move, NormalizedSkewb, Orientation, Skewb, CENTERS, FIXED_CORNERS,
+    FLOATING_CORNERS,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Twist {
+    Clockwise,
+    Anticlockwise,
+}
+
+impl Twist {
+    // How a corner with the given orientation is twisted, looking at the corner. Moving the U or
+    // D sticker onto the L or R face is clockwise for the fixed corners but anticlockwise for the
+    // floating ones, since they are mirror images of each other.
+    pub fn of(c: Corner, o: Orientation) -> Option<Twist> {
+        let fixed = FIXED_CORNERS.contains(&c);
+        match o {
+            Orientation::UD => None,
+            Orientation::LR if fixed => Some(Twist::Clockwise),
+            Orientation::FB if !fixed => Some(Twist::Clockwise),
+            _ => Some(Twist::Anticlockwise),
+        }
+    }
+}
+
+impl fmt::Display for Twist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Twist::Clockwise => write!(f, "+"),
+            Twist::Anticlockwise => write!(f, "-"),
+        }
+    }
+}
+
+// Corners that each move to the next, the last one back to the first, along with the twist the
+// pieces pick up going all the way round.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CornerCycle {
+    pub corners: Vec<Corner>,
+    pub twist: Option<Twist>,
+}
+
+// What a sequence of moves does to a solved puzzle, as cycles of pieces, and how many times it has
+// to be repeated to get back to solved.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CycleStructure {
+    // Centres that each move to the next, the last one back to the first.
+    pub centers: Vec<Vec<Center>>,
+    // Floating corners that move or are twisted in place.
+    pub floating_corners: Vec<CornerCycle>,
+    // The fixed corners never move, but may be twisted.
+    pub fixed_twists: Vec<(Corner, Twist)>,
+    pub order: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize { a / gcd(a, b) * b }
+
+// The cycles of a permutation where the piece at position i moves to position to[i], leaving out
+// pieces that stay put unless keep says otherwise.
+fn cycles(to: &[usize], keep: &dyn Fn(usize) -> bool) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; to.len()];
+    let mut cycles = vec![];
+    for start in 0..to.len() {
+        if seen[start] {
+            continue;
+        }
+        let mut cycle = vec![];
+        let mut i = start;
+        while !seen[i] {
+            seen[i] = true;
+            cycle.push(i);
+            i = to[i];
+        }
+        if cycle.len() > 1 || keep(start) {
+            cycles.push(cycle);
+        }
+    }
+    cycles
+}
+
+impl CycleStructure {
+    // The cycles of a state, taken as what some moves did to a solved puzzle.
+    pub fn of(state: &NormalizedSkewb) -> CycleStructure {
+        let solved = NormalizedSkewb::new();
+        let mut order = 1;
+
+        let center_to: Vec<usize> = solved
+            .center_pieces
+            .iter()
+            .map(|c| state.center_pieces.iter().position(|d| d == c).unwrap())
+            .collect();
+        let centers: Vec<Vec<Center>> = cycles(&center_to, &|_| false)
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|i| CENTERS[i]).collect())
+            .collect();
+        for cycle in centers.iter() {
+            order = lcm(order, cycle.len());
+        }
+
+        let floating_to: Vec<usize> = (0..4)
+            .map(|p| state.floating_pieces.iter().position(|&q| q == p).unwrap())
+            .collect();
+        let twisted = |i: usize| state.floating_orientations[i] != Orientation::UD;
+        let floating_corners: Vec<CornerCycle> = cycles(&floating_to, &twisted)
+            .into_iter()
+            .map(|cycle| {
+                let mut twist = Orientation::UD;
+                for &i in cycle.iter() {
+                    twist += state.floating_orientations[i];
+                }
+                let corners: Vec<Corner> = cycle.into_iter().map(|i| FLOATING_CORNERS[i]).collect();
+                CornerCycle {
+                    twist: Twist::of(corners[0], twist),
+                    corners,
+                }
+            })
+            .collect();
+        for cycle in floating_corners.iter() {
+            let turns = if cycle.twist.is_some() { 3 } else { 1 };
+            order = lcm(order, cycle.corners.len() * turns);
+        }
+
+        let fixed_twists: Vec<(Corner, Twist)> = FIXED_CORNERS
+            .iter()
+            .zip(state.fixed_orientations.iter())
+            .filter_map(|(&c, &o)| Twist::of(c, o).map(|twist| (c, twist)))
+            .collect();
+        if !fixed_twists.is_empty() {
+            order = lcm(order, 3);
+        }
+
+        CycleStructure {
+            centers,
+            floating_corners,
+            fixed_twists,
+            order,
+        }
+    }
+
+    pub fn of_moves(moves: &[Move]) -> CycleStructure {
+        CycleStructure::of(&NormalizedSkewb::after(moves))
+    }
+}
+
+// Written like (U F R) (UFL UBR)+ UBL- for a centre 3-cycle, a floating corner 2-cycle that
+// twists them clockwise on the way round, and a fixed corner twisted anticlockwise.
+impl fmt::Display for CycleStructure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![];
+        for cycle in self.centers.iter() {
+            let names: Vec<String> = cycle.iter().map(|c| format!("{:?}", c)).collect();
+            parts.push(format!("({})", names.join(" ")));
+        }
+        for cycle in self.floating_corners.iter() {
+            let names: Vec<String> = cycle.corners.iter().map(|&c| corner_name(c)).collect();
+            let twist = cycle.twist.map_or(String::new(), |t| t.to_string());
+            parts.push(format!("({}){}", names.join(" "), twist));
+        }
+        for &(c, twist) in self.fixed_twists.iter() {
+            parts.push(format!("{}{}", corner_name(c), twist));
+        }
+        if parts.is_empty() {
+            write!(f, "()")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+// What differs between two states, position by position.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diff {
+    // Centres showing a different colour.
+    pub centers: Vec<Center>,
+    // Floating corners holding a different piece.
+    pub floating_corners: Vec<Corner>,
+    // Corners holding the same piece turned another way, with how much the first state's piece is
+    // twisted from the second's.
+    pub twisted_corners: Vec<(Corner, Twist)>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.centers.is_empty()
+            && self.floating_corners.is_empty()
+            && self.twisted_corners.is_empty()
+    }
+
+    // Like "2 centres swapped, fixed corner UBL twisted clockwise".
+    pub fn summary(&self) -> String {
+        let moved = |n: usize, pieces: &str| match n {
+            2 => format!("2 {} swapped", pieces),
+            n => format!("{} {} misplaced", n, pieces),
+        };
+        let mut parts = vec![];
+        if !self.centers.is_empty() {
+            parts.push(moved(self.centers.len(), "centres"));
+        }
+        if !self.floating_corners.is_empty() {
+            parts.push(moved(self.floating_corners.len(), "floating corners"));
+        }
+        for &(c, twist) in self.twisted_corners.iter() {
+            let kind = if FIXED_CORNERS.contains(&c) {
+                "fixed"
+            } else {
+                "floating"
+            };
+            let way = match twist {
+                Twist::Clockwise => "clockwise",
+                Twist::Anticlockwise => "anticlockwise",
+            };
+            parts.push(format!(
+                "{} corner {} twisted {}",
+                kind,
+                corner_name(c),
+                way
+            ));
+        }
+        if parts.is_empty() {
+            "no differences".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+impl NormalizedSkewb {
+    pub fn diff(&self, other: &NormalizedSkewb) -> Diff {
+        let centers = CENTERS
+            .iter()
+            .zip(self.center_pieces.iter().zip(other.center_pieces.iter()))
+            .filter(|&(_, (a, b))| a != b)
+            .map(|(&c, _)| c)
+            .collect();
+        let floating_corners = (0..4)
+            .filter(|&i| self.floating_pieces[i] != other.floating_pieces[i])
+            .map(|i| FLOATING_CORNERS[i])
+            .collect();
+        let mut twisted_corners = vec![];
+        for (i, &c) in FIXED_CORNERS.iter().enumerate() {
+            let o = self.fixed_orientations[i] - other.fixed_orientations[i];
+            if let Some(twist) = Twist::of(c, o) {
+                twisted_corners.push((c, twist));
+            }
+        }
+        for (i, &c) in FLOATING_CORNERS.iter().enumerate() {
+            let o = self.floating_orientations[i] - other.floating_orientations[i];
+            if self.floating_pieces[i] == other.floating_pieces[i] {
+                if let Some(twist) = Twist::of(c, o) {
+                    twisted_corners.push((c, twist));
+                }
+            }
+        }
+        Diff {
+            centers,
+            floating_corners,
+            twisted_corners,
+        }
+    }
+}
+
+impl Skewb {
+    // The diff of the two states, each turned round into the canonical position first.
+    pub fn diff(&self, other: &Skewb) -> Diff {
+        let normalized = |skewb: &Skewb| {
+            let mut skewb = skewb.clone();
+            skewb.rotate_to_canonical();
+            skewb.normalize()
+        };
+        normalized(self).diff(&normalized(other))
+    }
+}
+
+#[test]
+fn one_move() {
+    use skewb::Direction;
+
+    let structure = CycleStructure::of_moves(&[Move::new((0, 0, 0), Direction::LR)]);
+    assert_eq!(3, structure.order);
+    assert_eq!(1, structure.centers.len());
+    assert_eq!(3, structure.centers[0].len());
+    assert_eq!(vec![((0, 0, 0), Twist::Clockwise)], structure.fixed_twists);
+    assert_eq!(1, structure.floating_corners.len());
+    assert_eq!(3, structure.floating_corners[0].corners.len());
+    assert_eq!(None, structure.floating_corners[0].twist);
+    assert_eq!("(U L B) (UFL DBL UBR) UBL+", structure.to_string());
+
+    assert_eq!("()", CycleStructure::of_moves(&[]).to_string());
+    assert_eq!(1, CycleStructure::of_moves(&[]).order);
+}
+
+#[test]
+fn order_returns_to_solved() {
+    use solver::scramble_moves;
+
+    for seed in 0..30 {
+        let moves = scramble_moves(seed, 1 + seed as usize % 7);
+        let structure = CycleStructure::of_moves(&moves);
+        let mut state = NormalizedSkewb::new();
+        for repetition in 1..=structure.order {
+            for move_ in moves.iter() {
+                state.do_move(move_);
+            }
+            assert_eq!(repetition == structure.order, state.is_solved());
+        }
+    }
+}
+
+#[test]
+fn diffs() {
+    use skewb::Direction;
+
+    let solved = NormalizedSkewb::new();
+    assert!(solved.diff(&solved).is_empty());
+    assert_eq!("no differences", solved.diff(&solved).summary());
+
+    let mut state = NormalizedSkewb::new();
+    state.center_pieces.swap(0, 1);
+    state.fixed_orientations[0] = Orientation::LR;
+    let diff = state.diff(&solved);
+    assert_eq!(vec![Center::U, Center::F], diff.centers);
+    assert!(diff.floating_corners.is_empty());
+    assert_eq!(vec![((0, 0, 0), Twist::Clockwise)], diff.twisted_corners);
+    assert_eq!(
+        "2 centres swapped, fixed corner UBL twisted clockwise",
+        diff.summary()
+    );
+    assert_eq!(
+        vec![((0, 0, 0), Twist::Anticlockwise)],
+        solved.diff(&state).twisted_corners
+    );
+
+    let mut turned = NormalizedSkewb::new();
+    turned.do_move(&Move::new((0, 0, 0), Direction::LR));
+    assert_eq!(
+        "3 centres misplaced, 3 floating corners misplaced, fixed corner UBL twisted clockwise",
+        turned.diff(&solved).summary()
+    );
+    let mut skewb = Skewb::new();
+    skewb.do_move(&Move::new((0, 0, 0), Direction::LR));
+    assert_eq!(turned.diff(&solved), skewb.diff(&Skewb::new()));
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use skewb::{Move, NormalizedSkewb};
+use solver::{SearchConfig, SearchError, Solver};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchResult {
+    pub solution: Result<Vec<Move>, SearchError>,
+    // How long this one state took to solve.
+    pub elapsed: Duration,
+}
+
+// One thread per CPU core, or just one if that can't be found out.
+pub(crate) fn default_threads() -> usize { thread::available_parallelism().map_or(1, |n| n.get()) }
+
+// Solves every state on all CPU cores and returns the results in the same order as the states.
+// The solver, and whatever tables it holds, is shared read-only between the worker threads, and
+// so is config, so its progress callback gets called from several threads at once.
+pub fn solve_batch<S, I>(solver: &S, states: I, config: &SearchConfig) -> Vec<BatchResult>
+where
+    S: Solver + Sync,
+    I: IntoIterator<Item = NormalizedSkewb>,
+{
+    solve_batch_on(default_threads(), solver, states, config)
+}
+
+fn solve_batch_on<S, I>(
+    threads: usize,
+    solver: &S,
+    states: I,
+    config: &SearchConfig,
+) -> Vec<BatchResult>
+where
+    S: Solver + Sync,
+    I: IntoIterator<Item = NormalizedSkewb>,
+{
+    let states: Vec<NormalizedSkewb> = states.into_iter().collect();
+    let next = AtomicUsize::new(0);
+
+    let mut results: Vec<(usize, BatchResult)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(states.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= states.len() {
+                            break;
+                        }
+                        let start = Instant::now();
+                        let solution = solver.solve(&states[i], config);
+                        let elapsed = start.elapsed();
+                        results.push((i, BatchResult { solution, elapsed }));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[test]
+fn results_are_in_input_order() {
+    use solver::{scramble, TableLookup};
+
+    let solver = TableLookup::new();
+    let config = SearchConfig::default();
+    let states: Vec<NormalizedSkewb> = (0..200).map(|seed| scramble(seed, 30)).collect();
+    let results = solve_batch_on(4, &solver, states.clone(), &config);
+
+    assert_eq!(states.len(), results.len());
+    for (state, result) in states.iter().zip(results.iter()) {
+        assert_eq!(solver.solve(state, &config), result.solution);
+    }
+}
+
+#[test]
+fn empty_batch() {
+    use solver::Iddfs;
+
+    let results = solve_batch(&Iddfs, vec![], &SearchConfig::default());
+    assert!(results.is_empty());
+}
@@ -0,0 +1,39 @@
+extern crate skewb;
+
+use std::env;
+use std::process;
+
+use skewb::skewb::{Corner, FIXED_CORNERS};
+use skewb::table::DepthReport;
+
+// Prints how many states are at each distance from solved. With no arguments this covers the
+// whole puzzle; otherwise only the subgroup generated by the given fixed corners, written like
+// 000 or 101.
+fn main() {
+    let mut corners: Vec<Corner> = vec![];
+    for arg in env::args().skip(1) {
+        let digits: Vec<u8> = arg.bytes().map(|b| b.wrapping_sub(b'0')).collect();
+        match digits[..] {
+            [a, b, c] if FIXED_CORNERS.contains(&(a, b, c)) => corners.push((a, b, c)),
+            _ => {
+                eprintln!("{} is not a fixed corner. Try one of 000 011 101 110.", arg);
+                process::exit(1);
+            }
+        }
+    }
+    if corners.is_empty() {
+        corners = FIXED_CORNERS.to_vec();
+    }
+
+    let report = DepthReport::for_corners(&corners);
+    println!("Generated by {:?}", corners);
+    for (depth, count) in report.histogram.iter().enumerate() {
+        println!("{:>3} {:>10}", depth, count);
+    }
+    println!("Group size: {}", report.group_size);
+    println!("Diameter: {}", report.diameter);
+    println!("{} antipodes:", report.antipodes.len());
+    for antipode in report.antipodes.iter() {
+        println!("{:?}", antipode);
+    }
+}
@@ -12,14 +12,17 @@ use piston::window::WindowSettings;
 
 use graphics::Graphics;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use skewb::drawer::Drawer;
 use skewb::skewb::Color;
 use skewb::skewb::NormalizedSkewb;
 use skewb::skewb::Orientation;
+use skewb::solver::{Iddfs, Progress, SearchConfig, Solver};
 
 fn main() {
     // A skewb!
-    let mut scrambled = NormalizedSkewb {
+    let scrambled = NormalizedSkewb {
         center_pieces: [Color::Y, Color::G, Color::R, Color::O, Color::B, Color::W],
         fixed_orientations: [
             Orientation::UD,
@@ -34,17 +37,30 @@ fn main() {
             Orientation::LR,
         ],
         floating_pieces: [1, 3, 2, 0],
+        center_orientations: None,
     };
 
     // Solve it!
-    println!("Solving. This might take a minute.");
-    if let Some(solution) = scrambled.solution() {
-        println!("Found a solution:");
-        for move_ in solution.iter() {
-            println!("{:?}", move_);
+    let depth = AtomicUsize::new(usize::MAX);
+    let config = SearchConfig {
+        progress: Some(Box::new(move |progress: Progress| {
+            if depth.swap(progress.depth, Ordering::Relaxed) != progress.depth {
+                println!(
+                    "Looking for {}-move solutions ({} states searched)",
+                    progress.depth, progress.nodes_expanded
+                );
+            }
+        })),
+        ..SearchConfig::default()
+    };
+    match Iddfs.solve(&scrambled, &config) {
+        Ok(solution) => {
+            println!("Found a solution:");
+            for move_ in solution.iter() {
+                println!("{:?}", move_);
+            }
         }
-    } else {
-        println!("No solution found.");
+        Err(error) => println!("No solution found: {:?}", error),
     }
 
     // Draw it!
@@ -0,0 +1,27 @@
+extern crate skewb;
+
+use std::env;
+use std::process;
+
+use skewb::notation::{format_wca, parse_wca};
+use skewb::solver::{SearchConfig, TableLookup};
+use skewb::wca::WcaSolver;
+
+// Prints a shortest solution in WCA notation for a scramble in WCA notation, given like
+// R U' L B.
+fn main() {
+    let text = env::args().skip(1).collect::<Vec<_>>().join(" ");
+    let scramble = match parse_wca(&text) {
+        Ok(scramble) => scramble,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+
+    let solver = WcaSolver::new(TableLookup::new());
+    match solver.solve_scramble(&scramble, &SearchConfig::default()) {
+        Ok(solution) => println!("{}", format_wca(&solution)),
+        Err(error) => println!("No solution found: {:?}", error),
+    }
+}
@@ -0,0 +1,233 @@
+use skewb::{Center, Corner, Move, NormalizedSkewb, Orientation, FIXED_CORNERS, FLOATING_CORNERS};
+
+// The effect a commutator is wanted for, starting from solved.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    // Three centres cycled and nothing else changed. If the centres are given, the one at the
+    // first position must go to the second, the second to the third and the third to the first.
+    CenterCycle(Option<[Center; 3]>),
+    // Two corners twisted in place and nothing else changed, optionally exactly the given ones.
+    CornerTwist(Option<[Corner; 2]>),
+}
+
+impl Pattern {
+    // Whether the state has the right kind of effect, ignoring which pieces it affects.
+    fn has_shape(&self, state: &NormalizedSkewb) -> bool {
+        let solved = NormalizedSkewb::new();
+        if state.floating_pieces != solved.floating_pieces {
+            return false;
+        }
+        match *self {
+            Pattern::CenterCycle(_) => {
+                state.fixed_orientations == solved.fixed_orientations
+                    && state.floating_orientations == solved.floating_orientations
+                    && (0..6)
+                        .filter(|&i| state.center_pieces[i] != solved.center_pieces[i])
+                        .count()
+                        == 3
+            }
+            Pattern::CornerTwist(_) => {
+                state.center_pieces == solved.center_pieces && twisted_corners(state).len() == 2
+            }
+        }
+    }
+
+    pub fn matches(&self, state: &NormalizedSkewb) -> bool {
+        if !self.has_shape(state) {
+            return false;
+        }
+        match *self {
+            Pattern::CenterCycle(None) | Pattern::CornerTwist(None) => true,
+            Pattern::CenterCycle(Some(centers)) => {
+                let solved = NormalizedSkewb::new();
+                (0..3).all(|i| {
+                    let from = NormalizedSkewb::center_to_i(centers[i]);
+                    let to = NormalizedSkewb::center_to_i(centers[(i + 1) % 3]);
+                    state.center_pieces[to] == solved.center_pieces[from]
+                })
+            }
+            Pattern::CornerTwist(Some(corners)) => {
+                let mut twisted = twisted_corners(state);
+                let mut corners = corners.to_vec();
+                twisted.sort();
+                corners.sort();
+                twisted == corners
+            }
+        }
+    }
+}
+
+fn twisted_corners(state: &NormalizedSkewb) -> Vec<Corner> {
+    let fixed = FIXED_CORNERS.iter().zip(state.fixed_orientations.iter());
+    let floating = FLOATING_CORNERS
+        .iter()
+        .zip(state.floating_orientations.iter());
+    fixed
+        .chain(floating)
+        .filter(|&(_, &o)| o != Orientation::UD)
+        .map(|(&c, _)| c)
+        .collect()
+}
+
+// [C: [A, B]], which is C A B A' B' C'. Without a setup it's just [A, B].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Commutator {
+    pub setup: Vec<Move>,
+    pub a: Vec<Move>,
+    pub b: Vec<Move>,
+}
+
+fn inverse(moves: &[Move]) -> Vec<Move> { moves.iter().rev().map(Move::inverse).collect() }
+
+impl Commutator {
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = self.setup.clone();
+        moves.extend_from_slice(&self.a);
+        moves.extend_from_slice(&self.b);
+        moves.extend(inverse(&self.a));
+        moves.extend(inverse(&self.b));
+        moves.extend(inverse(&self.setup));
+        moves
+    }
+
+    // What the commutator does to a solved puzzle.
+    pub fn effect(&self) -> NormalizedSkewb { NormalizedSkewb::after(&self.moves()) }
+}
+
+// Every sequence of one to max_length moves that never turns the same corner twice in a row.
+fn sequences(max_length: usize) -> Vec<Vec<Move>> {
+    let mut sequences: Vec<Vec<Move>> = vec![];
+    let mut previous: Vec<Vec<Move>> = vec![vec![]];
+    for _ in 0..max_length {
+        let mut next = vec![];
+        for sequence in previous.iter() {
+            for move_ in Move::all() {
+                if sequence.last().is_none_or(|m| m.corner() != move_.corner()) {
+                    let mut longer = sequence.clone();
+                    longer.push(move_);
+                    next.push(longer);
+                }
+            }
+        }
+        sequences.extend(next.iter().cloned());
+        previous = next;
+    }
+    sequences
+}
+
+// Every [A, B] with A and B at most the given lengths whose effect on a solved puzzle matches the
+// pattern, followed by every [C: [A, B]] with a setup C of at most max_setup moves that does.
+// A setup only changes which pieces are affected, not the kind of effect, so setups are only
+// tried on the commutators whose effect is of the right kind, but on every one of those.
+// Shorter commutators come first.
+pub fn find_commutators(
+    pattern: &Pattern,
+    max_a: usize,
+    max_b: usize,
+    max_setup: usize,
+) -> Vec<Commutator> {
+    let mut found = vec![];
+    let mut bases = vec![];
+    let b_sequences = sequences(max_b);
+    for a in sequences(max_a).into_iter() {
+        for b in b_sequences.iter() {
+            if a.last().unwrap().corner() == b[0].corner() {
+                continue;
+            }
+            let commutator = Commutator {
+                setup: vec![],
+                a: a.clone(),
+                b: b.clone(),
+            };
+            let effect = commutator.effect();
+            if !pattern.has_shape(&effect) {
+                continue;
+            }
+            if pattern.matches(&effect) {
+                found.push(commutator.clone());
+            }
+            bases.push(commutator);
+        }
+    }
+
+    for setup in sequences(max_setup).into_iter() {
+        for base in bases.iter() {
+            let commutator = Commutator {
+                setup: setup.clone(),
+                ..base.clone()
+            };
+            if pattern.matches(&commutator.effect()) {
+                found.push(commutator);
+            }
+        }
+    }
+
+    found.sort_by_key(|commutator| commutator.moves().len());
+    found
+}
+
+#[test]
+fn center_cycles() {
+    let found = find_commutators(&Pattern::CenterCycle(None), 3, 3, 0);
+    assert!(!found.is_empty());
+    for commutator in found.iter() {
+        assert!(commutator.setup.is_empty());
+        let effect = commutator.effect();
+        assert!(Pattern::CenterCycle(None).matches(&effect));
+        assert_eq!(
+            3,
+            (0..6)
+                .filter(|&i| effect.center_pieces[i] != NormalizedSkewb::new().center_pieces[i])
+                .count()
+        );
+    }
+    assert!(found
+        .windows(2)
+        .all(|w| w[0].moves().len() <= w[1].moves().len()));
+
+    let wanted = Pattern::CenterCycle(Some([Center::U, Center::F, Center::R]));
+    let with_setups = find_commutators(&wanted, 3, 3, 1);
+    assert!(!with_setups.is_empty());
+    for commutator in with_setups.iter() {
+        assert!(wanted.matches(&commutator.effect()));
+    }
+    let backwards = Pattern::CenterCycle(Some([Center::U, Center::R, Center::F]));
+    assert!(with_setups.iter().all(|c| !backwards.matches(&c.effect())));
+
+    // Every [A, B] under a setup is found, not just one for each effect.
+    let mut alternatives = 0;
+    for commutator in with_setups.iter().filter(|c| !c.setup.is_empty()) {
+        let inner = Commutator {
+            setup: vec![],
+            ..commutator.clone()
+        }
+        .effect();
+        for other in found.iter().filter(|c| c.effect() == inner) {
+            let moved = Commutator {
+                setup: commutator.setup.clone(),
+                ..other.clone()
+            };
+            assert!(with_setups.contains(&moved));
+            alternatives += 1;
+        }
+    }
+    assert!(alternatives > with_setups.iter().filter(|c| !c.setup.is_empty()).count());
+}
+
+#[test]
+fn corner_twists() {
+    let found = find_commutators(&Pattern::CornerTwist(None), 3, 3, 0);
+    assert!(!found.is_empty());
+    for commutator in found.iter() {
+        let effect = commutator.effect();
+        assert_eq!(NormalizedSkewb::new().center_pieces, effect.center_pieces);
+        assert_eq!(2, twisted_corners(&effect).len());
+    }
+
+    let effect = found[0].effect();
+    let corners = twisted_corners(&effect);
+    assert!(Pattern::CornerTwist(Some([corners[0], corners[1]])).matches(&effect));
+    assert!(!Pattern::CornerTwist(Some([corners[0], corners[0]])).matches(&effect));
+
+    assert!(find_commutators(&Pattern::CornerTwist(None), 2, 3, 0).is_empty());
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use skewb::{Move, NormalizedSkewb};
+
+// Tells states apart, centre orientations and all, without keeping the whole state.
+type Key = (usize, Option<[u8; 6]>);
+
+fn key(state: &NormalizedSkewb) -> Key { (state.rank(), state.center_orientations) }
+
+// Every state exactly some number of moves from a start, each with one shortest way to reach it.
+// The layers before the last are searched when the iterator is made, but the last one is only
+// found as it's asked for, so taking a few states far from the start is cheap next to building a
+// DistanceTable. Only the move that first reached each state is kept, and the way to a state is
+// walked back from it when it's yielded.
+pub struct StatesAtDepth {
+    // Every state found so far, with the move that reached it, or None for the start.
+    parents: HashMap<Key, Option<Move>>,
+    // The layer just before the one being found, and how much of it has been expanded.
+    previous: Vec<NormalizedSkewb>,
+    next: usize,
+    // The neighbours of the last expanded state that haven't been looked at yet.
+    pending: Vec<(Move, NormalizedSkewb)>,
+    // The start, when it's the layer asked for.
+    start: Option<NormalizedSkewb>,
+}
+
+impl StatesAtDepth {
+    fn new(start: &NormalizedSkewb, depth: usize) -> StatesAtDepth {
+        let mut states = StatesAtDepth {
+            parents: HashMap::new(),
+            previous: vec![],
+            next: 0,
+            pending: vec![],
+            start: None,
+        };
+        if depth == 0 {
+            states.start = Some(start.clone());
+            return states;
+        }
+        states.parents.insert(key(start), None);
+        states.previous = vec![start.clone()];
+        for _ in 1..depth {
+            let mut layer = vec![];
+            while let Some(state) = states.next_state() {
+                layer.push(state);
+            }
+            states.previous = layer;
+            states.next = 0;
+        }
+        states
+    }
+
+    // The next state of the layer being found, without the way to it.
+    fn next_state(&mut self) -> Option<NormalizedSkewb> {
+        loop {
+            while let Some((move_, state)) = self.pending.pop() {
+                let k = key(&state);
+                if self.parents.contains_key(&k) {
+                    continue;
+                }
+                self.parents.insert(k, Some(move_));
+                return Some(state);
+            }
+            let state = self.previous.get(self.next)?;
+            self.pending = state.neighbors();
+            self.pending.reverse();
+            self.next += 1;
+        }
+    }
+
+    // The moves from the start to a state that has been found.
+    fn path(&self, state: &NormalizedSkewb) -> Vec<Move> {
+        let mut moves = vec![];
+        let mut state = state.clone();
+        while let Some(move_) = self.parents[&key(&state)] {
+            state.undo_move(&move_);
+            moves.push(move_);
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+impl Iterator for StatesAtDepth {
+    type Item = (NormalizedSkewb, Vec<Move>);
+
+    fn next(&mut self) -> Option<(NormalizedSkewb, Vec<Move>)> {
+        if let Some(start) = self.start.take() {
+            return Some((start, vec![]));
+        }
+        let state = self.next_state()?;
+        let moves = self.path(&state);
+        Some((state, moves))
+    }
+}
+
+impl NormalizedSkewb {
+    // Breadth first from self, every state whose shortest solution back to self is exactly depth
+    // moves long, with the moves that reach it.
+    pub fn states_at_depth(&self, depth: usize) -> StatesAtDepth { StatesAtDepth::new(self, depth) }
+}
+
+#[test]
+fn layer_sizes() {
+    let solved = NormalizedSkewb::new();
+    let sizes: Vec<usize> = (0..5).map(|d| solved.states_at_depth(d).count()).collect();
+    assert_eq!(vec![1, 8, 48, 288, 1728], sizes);
+    assert_eq!(
+        vec![(solved.clone(), vec![])],
+        solved.states_at_depth(0).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn paths_are_optimal() {
+    use solver::{scramble, Iddfs, SearchConfig};
+
+    let start = scramble(4, 10);
+    for (state, moves) in start.states_at_depth(3).step_by(37) {
+        assert_eq!(3, moves.len());
+        assert_eq!(start.applied(&moves), state);
+        let distance = Iddfs
+            .solve_puzzle(&state, &start, &Move::all(), &SearchConfig::default())
+            .unwrap()
+            .len();
+        assert_eq!(3, distance);
+    }
+}
+
+#[test]
+fn lazy_far_layers() {
+    // The 59,304 states of the last layer are never all found.
+    let far: Vec<_> = NormalizedSkewb::new().states_at_depth(6).take(3).collect();
+    assert_eq!(3, far.len());
+    assert!(far.iter().all(|(_, moves)| moves.len() == 6));
+}
+
+#[test]
+fn supercube_layers() {
+    // The way to each state is walked back from it, which has to turn the centres back too.
+    let supercube = NormalizedSkewb::supercube();
+    for depth in 0..4 {
+        let layer: Vec<_> = supercube.states_at_depth(depth).collect();
+        assert_eq!(
+            NormalizedSkewb::new().states_at_depth(depth).count(),
+            layer.len()
+        );
+        for (state, moves) in layer.iter() {
+            assert_eq!(&supercube.applied(moves), state);
+        }
+    }
+}
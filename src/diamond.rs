@@ -0,0 +1,329 @@
+use puzzle::TwistyPuzzle;
+use skewb::{
+    center_direction, center_normal, Center, Corner, Move, NormalizedSkewb, Orientation, CENTERS,
+    FIXED_CORNERS, FLOATING_CORNERS,
+};
+use solver::{Iddfs, SearchConfig, SearchError};
+
+// The faces of a Skewb Diamond, one where each corner of a skewb is, fixed ones first. In
+// facelets a face is written as its index here.
+pub const FACES: [Corner; 8] = [
+    FIXED_CORNERS[0],
+    FIXED_CORNERS[1],
+    FIXED_CORNERS[2],
+    FIXED_CORNERS[3],
+    FLOATING_CORNERS[0],
+    FLOATING_CORNERS[1],
+    FLOATING_CORNERS[2],
+    FLOATING_CORNERS[3],
+];
+
+fn face_to_i(face: Corner) -> usize { FACES.iter().position(|&f| f == face).unwrap() }
+
+// The faces around a vertex, starting with the one the clockwise side of its marker points into
+// and going clockwise, when the marker is turned the given number of times.
+fn faces_around(vertex: Center, turns: u8) -> [Corner; 4] {
+    let n = center_normal(vertex);
+    let mut faces = [(0, 0, 0); 4];
+    for (j, face) in faces.iter_mut().enumerate() {
+        let a = center_direction(vertex, turns + j as u8);
+        let b = center_direction(vertex, turns + j as u8 + 1);
+        let x: Vec<u8> = (0..3)
+            .map(|i| ((n[i] + a[i] + b[i] + 1) / 2) as u8)
+            .collect();
+        *face = (x[0], x[1], x[2]);
+    }
+    faces
+}
+
+fn is_even(permutation: &[usize]) -> bool {
+    let mut inversions = 0;
+    for i in 0..permutation.len() {
+        for j in i + 1..permutation.len() {
+            if permutation[i] > permutation[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 == 0
+}
+
+// The octahedral dual of the skewb. Turning a face of the diamond moves the same pieces as turning
+// the corner of a skewb where that face is: the face centres are where the skewb's corners are,
+// and the vertices where its centres are. A face centre has just one sticker, so its twist can't
+// be seen, but a vertex has four, so which way round it is can, as on a supercube.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SkewbDiamond {
+    // A supercube whose corners are never twisted.
+    state: NormalizedSkewb,
+}
+
+impl Default for SkewbDiamond {
+    fn default() -> SkewbDiamond { SkewbDiamond::new() }
+}
+
+impl SkewbDiamond {
+    pub fn new() -> SkewbDiamond {
+        SkewbDiamond {
+            state: NormalizedSkewb::supercube(),
+        }
+    }
+
+    // The diamond that the moves reaching a supercube state leave a solved diamond in, or None if
+    // the state isn't a supercube's.
+    pub fn from_supercube(state: &NormalizedSkewb) -> Option<SkewbDiamond> {
+        state.center_orientations?;
+        let mut diamond = SkewbDiamond {
+            state: state.clone(),
+        };
+        diamond.normalize();
+        Some(diamond)
+    }
+
+    // Forgets the twists of the face centres, so that diamonds that look the same are equal.
+    fn normalize(&mut self) {
+        self.state.fixed_orientations = [Orientation::UD; 4];
+        self.state.floating_orientations = [Orientation::UD; 4];
+    }
+
+    // The face whose centre is on the given face.
+    pub fn face_center(&self, face: Corner) -> Corner {
+        match FLOATING_CORNERS.iter().position(|&c| c == face) {
+            Some(i) => FLOATING_CORNERS[self.state.floating_pieces[i]],
+            None => face,
+        }
+    }
+
+    // The vertex whose piece is at the given vertex, and how many quarter turns clockwise it is
+    // from the way it's held at home.
+    pub fn vertex(&self, vertex: Center) -> (Center, u8) {
+        let i = NormalizedSkewb::center_to_i(vertex);
+        let solved = NormalizedSkewb::new();
+        let home = solved
+            .center_pieces
+            .iter()
+            .position(|&c| c == self.state.center_pieces[i])
+            .unwrap();
+        (CENTERS[home], self.state.center_orientations.unwrap()[i])
+    }
+
+    pub fn do_move(&mut self, move_: &Move) {
+        self.state.do_move(move_);
+        self.normalize();
+    }
+    pub fn undo_move(&mut self, move_: &Move) {
+        self.state.undo_move(move_);
+        self.normalize();
+    }
+
+    pub fn is_solved(&self) -> bool { *self == SkewbDiamond::new() }
+
+    pub fn solution(&self) -> Result<Vec<Move>, SearchError> {
+        Iddfs.solve_any(self, &SearchConfig::default())
+    }
+
+    // The face each sticker belongs to, face by face in the order of FACES: the centre, then the
+    // vertex stickers on the U/D, L/R and B/F axes.
+    fn stickers(&self) -> [[usize; 4]; 8] {
+        let mut stickers = [[0; 4]; 8];
+        for (i, &face) in FACES.iter().enumerate() {
+            stickers[i][0] = face_to_i(self.face_center(face));
+        }
+        for &vertex in CENTERS.iter() {
+            let (home, turns) = self.vertex(vertex);
+            let now = faces_around(vertex, turns);
+            let before = faces_around(home, 0);
+            let axis = center_normal(vertex).iter().position(|&x| x != 0).unwrap();
+            for j in 0..4 {
+                stickers[face_to_i(now[j])][1 + axis] = face_to_i(before[j]);
+            }
+        }
+        stickers
+    }
+
+    // Every sticker written as the digit of the face it belongs to, four to a face: like
+    // "0000 1111 2222 3333 4444 5555 6666 7777" for a solved diamond.
+    pub fn to_facelets(&self) -> String {
+        let faces: Vec<String> = self
+            .stickers()
+            .iter()
+            .map(|face| face.iter().map(|s| s.to_string()).collect())
+            .collect();
+        faces.join(" ")
+    }
+
+    // Reads what to_facelets writes, ignoring whitespace. The diamond has to be held with the
+    // centres of the four fixed faces on their own faces.
+    pub fn from_facelets(text: &str) -> Result<SkewbDiamond, String> {
+        let digits: Vec<usize> = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| {
+                c.to_digit(8)
+                    .map(|d| d as usize)
+                    .ok_or_else(|| format!("'{}' is not a face", c))
+            })
+            .collect::<Result<_, _>>()?;
+        if digits.len() != 32 {
+            return Err(format!("expected 32 stickers, got {}", digits.len()));
+        }
+        let sticker = |face: Corner, k: usize| digits[4 * face_to_i(face) + k];
+
+        let mut state = NormalizedSkewb::supercube();
+        for (i, &face) in FIXED_CORNERS.iter().enumerate() {
+            if sticker(face, 0) != i {
+                return Err("the centres of the fixed faces must be on their own faces".to_string());
+            }
+        }
+        for (i, &face) in FLOATING_CORNERS.iter().enumerate() {
+            state.floating_pieces[i] = match sticker(face, 0).checked_sub(4) {
+                Some(piece) => piece,
+                None => return Err("a fixed face's centre is on a floating face".to_string()),
+            };
+        }
+
+        let solved = NormalizedSkewb::new();
+        let mut orientations = [0; 6];
+        let mut homes = [0; 6];
+        for (i, &vertex) in CENTERS.iter().enumerate() {
+            let axis = center_normal(vertex).iter().position(|&x| x != 0).unwrap();
+            let seen: Vec<usize> = faces_around(vertex, 0)
+                .iter()
+                .map(|&face| sticker(face, 1 + axis))
+                .collect();
+            let (home, turns) = CENTERS
+                .iter()
+                .flat_map(|&home| (0..4).map(move |turns| (home, turns)))
+                .find(|&(home, turns)| {
+                    let now = faces_around(vertex, turns);
+                    let before = faces_around(home, 0);
+                    (0..4).all(|j| {
+                        let k = now.iter().position(|&f| f == faces_around(vertex, 0)[j]);
+                        seen[j] == face_to_i(before[k.unwrap()])
+                    })
+                })
+                .ok_or_else(|| format!("the stickers around {:?} are not a vertex", vertex))?;
+            homes[i] = NormalizedSkewb::center_to_i(home);
+            orientations[i] = turns;
+            state.center_pieces[i] = solved.center_pieces[homes[i]];
+        }
+        state.center_orientations = Some(orientations);
+
+        let mut floating = state.floating_pieces.to_vec();
+        floating.sort();
+        let mut vertices = homes.to_vec();
+        vertices.sort();
+        if floating != [0, 1, 2, 3] || vertices != [0, 1, 2, 3, 4, 5] {
+            return Err("a piece is there twice".to_string());
+        }
+        if !is_even(&state.floating_pieces) || !is_even(&homes) {
+            return Err("the pieces are swapped in a way no turns can".to_string());
+        }
+        if orientations.iter().any(|&turns| turns % 2 == 1) {
+            return Err("a vertex is turned a quarter turn".to_string());
+        }
+        // Every turn turns two vertices half way round.
+        if orientations
+            .iter()
+            .map(|&turns| turns as usize)
+            .sum::<usize>()
+            % 4
+            != 0
+        {
+            return Err("a single vertex is turned half way round".to_string());
+        }
+        Ok(SkewbDiamond { state })
+    }
+}
+
+impl TwistyPuzzle for SkewbDiamond {
+    type Move = Move;
+
+    fn moves() -> Vec<Move> { Move::all() }
+    fn do_move(&mut self, move_: &Move) { SkewbDiamond::do_move(self, move_) }
+    fn undo_move(&mut self, move_: &Move) { SkewbDiamond::undo_move(self, move_) }
+    fn is_solved(&self) -> bool { SkewbDiamond::is_solved(self) }
+
+    fn redundant(move_stack: &[Move], move_: &Move, moves: &[Move]) -> bool {
+        <NormalizedSkewb as TwistyPuzzle>::redundant(move_stack, move_, moves)
+    }
+}
+
+#[test]
+fn turning_faces() {
+    use solver::scramble_moves;
+
+    let moves = scramble_moves(5, 12);
+    let mut diamond = SkewbDiamond::new();
+    for move_ in moves.iter() {
+        diamond.do_move(move_);
+    }
+    let supercube = NormalizedSkewb::supercube().applied(&moves);
+    assert_eq!(
+        Some(diamond.clone()),
+        SkewbDiamond::from_supercube(&supercube)
+    );
+    assert_eq!(None, SkewbDiamond::from_supercube(&NormalizedSkewb::new()));
+    for move_ in moves.iter().rev() {
+        diamond.undo_move(move_);
+    }
+    assert!(diamond.is_solved());
+
+    // Twisting a face centre in place can't be seen.
+    let mut twisted = NormalizedSkewb::supercube();
+    twisted.fixed_orientations[0] = Orientation::LR;
+    assert!(SkewbDiamond::from_supercube(&twisted).unwrap().is_solved());
+
+    let mut one_move = SkewbDiamond::new();
+    one_move.do_move(&Move::all()[0]);
+    let corner = Move::all()[0].corner();
+    assert_eq!(corner, one_move.face_center(corner));
+    assert!(!one_move.is_solved());
+}
+
+#[test]
+fn facelets() {
+    use solver::scramble_moves;
+
+    assert_eq!(
+        "0000 1111 2222 3333 4444 5555 6666 7777",
+        SkewbDiamond::new().to_facelets()
+    );
+    for seed in 0..10 {
+        let mut diamond = SkewbDiamond::new();
+        for move_ in scramble_moves(seed, 15).iter() {
+            diamond.do_move(move_);
+        }
+        let facelets = diamond.to_facelets();
+        assert_eq!(Ok(diamond), SkewbDiamond::from_facelets(&facelets));
+        let mut counts = [0; 8];
+        for c in facelets.chars().filter(|c| !c.is_whitespace()) {
+            counts[c.to_digit(8).unwrap() as usize] += 1;
+        }
+        assert_eq!([4; 8], counts);
+    }
+
+    assert!(SkewbDiamond::from_facelets("0000 1111").is_err());
+    assert!(SkewbDiamond::from_facelets("0000 1111 2222 3333 4444 5555 6666 7778").is_err());
+    assert!(SkewbDiamond::from_facelets("1000 0111 2222 3333 4444 5555 6666 7777").is_err());
+    assert!(SkewbDiamond::from_facelets("0000 1111 2222 3333 5444 4555 6666 7777").is_err());
+    let mut turned = SkewbDiamond::new();
+    turned.state.center_orientations = Some([2, 0, 0, 0, 0, 0]);
+    assert!(SkewbDiamond::from_facelets(&turned.to_facelets()).is_err());
+}
+
+#[test]
+fn solving_diamonds() {
+    use solver::scramble_moves;
+
+    let mut diamond = SkewbDiamond::new();
+    for move_ in scramble_moves(8, 5).iter() {
+        diamond.do_move(move_);
+    }
+    let solution = diamond.solution().unwrap();
+    assert!(solution.len() <= 5);
+    for move_ in solution.iter() {
+        diamond.do_move(move_);
+    }
+    assert!(diamond.is_solved());
+}
@@ -38,6 +38,11 @@ pub struct Drawer {
     edge_points: HashMap<Edge, Vec2d<f64>>,
     corner_stickers: HashMap<(Corner, Orientation), (Corner, Corner)>,
 }
+
+impl Default for Drawer {
+    fn default() -> Drawer { Drawer::new() }
+}
+
 impl Drawer {
     pub fn new() -> Drawer {
         let mut corner_points = HashMap::<Corner, Vec2d<f64>>::new();
@@ -157,6 +162,29 @@ impl Drawer {
                 c.transform,
                 g,
             );
+
+            // On a supercube, draw a line from the middle of the center towards the edge its
+            // marker points at.
+            if let Some(direction) = skewb.get_center_direction(center) {
+                let face = corners[0];
+                let face = [face.0, face.1, face.2];
+                let normal =
+                    (0..3).find(|&a| corners.iter().all(|x| [x.0, x.1, x.2][a] == face[a]));
+                let normal = normal.unwrap();
+                let marker = (0..3).find(|&a| direction[a] != 0).unwrap();
+                let mut ends = [face, face];
+                for (e, end) in ends.iter_mut().enumerate() {
+                    end[marker] = if direction[marker] > 0 { 1 } else { 0 };
+                    end[3 - normal - marker] = e as u8;
+                }
+                let [x1, y1] = self.edge_points[&Edge::new(
+                    (ends[0][0], ends[0][1], ends[0][2]),
+                    (ends[1][0], ends[1][1], ends[1][2]),
+                )];
+                let x0 = p.iter().map(|q| q[0]).sum::<f64>() / 4.0;
+                let y0 = p.iter().map(|q| q[1]).sum::<f64>() / 4.0;
+                Line::new(black, 2.0).draw([x0, y0, x1, y1], &c.draw_state, c.transform, g);
+            }
         }
     }
 }
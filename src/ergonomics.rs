@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use skewb::{Move, NormalizedSkewb};
+use solver::{SearchConfig, SearchError, Solver, TableLookup};
+
+// How hard a sequence of moves is to execute. Lower is easier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    // What particular moves cost. Any move not listed costs default_move_cost.
+    pub move_costs: HashMap<Move, i32>,
+    pub default_move_cost: i32,
+    // Charged for a move around a corner that isn't one of the last two turned, since the hands
+    // have to let go of the puzzle to reach it.
+    pub regrip_penalty: i32,
+    // Taken off for a move around the same corner as the move before, which is a quick double
+    // flick.
+    pub repeat_reward: i32,
+}
+
+impl Default for CostModel {
+    fn default() -> CostModel {
+        CostModel {
+            move_costs: HashMap::new(),
+            default_move_cost: 10,
+            regrip_penalty: 5,
+            repeat_reward: 3,
+        }
+    }
+}
+
+impl CostModel {
+    pub fn cost(&self, moves: &[Move]) -> i32 {
+        let mut cost = 0;
+        for (i, move_) in moves.iter().enumerate() {
+            cost += *self
+                .move_costs
+                .get(move_)
+                .unwrap_or(&self.default_move_cost);
+            if i >= 1 && moves[i - 1].corner() == move_.corner() {
+                cost -= self.repeat_reward;
+            } else if i >= 2 && moves[i - 2].corner() != move_.corner() {
+                cost += self.regrip_penalty;
+            }
+        }
+        cost
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RankedSolution {
+    pub moves: Vec<Move>,
+    pub cost: i32,
+}
+
+// Finds every shortest solution and prefers the ones that are easiest to execute, instead of
+// whichever one a search happens to find first.
+pub struct ErgonomicSolver {
+    lookup: TableLookup,
+    model: CostModel,
+}
+
+impl ErgonomicSolver {
+    pub fn new(lookup: TableLookup, model: CostModel) -> ErgonomicSolver {
+        ErgonomicSolver { lookup, model }
+    }
+
+    pub fn model(&self) -> &CostModel { &self.model }
+
+    // Every shortest solution, cheapest first. Solutions that cost the same stay in the order
+    // TableLookup finds them.
+    pub fn ranked_solutions(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<RankedSolution>, SearchError> {
+        let mut ranked: Vec<RankedSolution> = self
+            .lookup
+            .all_solutions(start, target, config)?
+            .into_iter()
+            .map(|moves| RankedSolution {
+                cost: self.model.cost(&moves),
+                moves,
+            })
+            .collect();
+        ranked.sort_by_key(|solution| solution.cost);
+        Ok(ranked)
+    }
+}
+
+impl Solver for ErgonomicSolver {
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        let mut ranked = self.ranked_solutions(start, target, config)?;
+        Ok(ranked.remove(0).moves)
+    }
+}
+
+#[test]
+fn cost_of_a_sequence() {
+    use skewb::Direction;
+
+    let r = Move::new((1, 1, 0), Direction::LR);
+    let r_ = Move::new((1, 1, 0), Direction::FB);
+    let u = Move::new((0, 0, 0), Direction::LR);
+    let l = Move::new((1, 0, 1), Direction::LR);
+    let mut model = CostModel::default();
+    model.move_costs.insert(r_, 12);
+
+    assert_eq!(0, model.cost(&[]));
+    assert_eq!(10 + 10 + 10, model.cost(&[r, u, r]));
+    assert_eq!(10 + 10 + 10 + 5, model.cost(&[r, u, l]));
+    assert_eq!(10 + 10 - 3, model.cost(&[r, r]));
+    assert_eq!(12 + 10 + 12, model.cost(&[r_, u, r_]));
+}
+
+#[test]
+fn cheapest_solutions_first() {
+    use skewb::Direction;
+    use solver::scramble;
+
+    let lookup = TableLookup::new();
+    let config = SearchConfig::default();
+    let start = scramble(4, 30);
+    let shortest = lookup.solve(&start, &config).unwrap();
+
+    let mut model = CostModel::default();
+    model
+        .move_costs
+        .insert(Move::new((0, 0, 0), Direction::LR), 100);
+    let solver = ErgonomicSolver::new(lookup, model);
+    let ranked = solver
+        .ranked_solutions(&start, &NormalizedSkewb::new(), &config)
+        .unwrap();
+
+    assert!(ranked.len() > 1);
+    assert!(ranked.windows(2).all(|w| w[0].cost <= w[1].cost));
+    for solution in ranked.iter() {
+        assert_eq!(shortest.len(), solution.moves.len());
+        assert!(start.applied(&solution.moves).is_solved());
+    }
+    assert_eq!(Ok(ranked[0].moves.clone()), solver.solve(&start, &config));
+    assert!(ranked[0].cost <= solver.model().cost(&shortest));
+}
@@ -0,0 +1,133 @@
+use skewb::{NormalizedSkewb, Orientation};
+
+// A state is also what some moves do to a solved puzzle, so states can be combined like the move
+// sequences that reach them. Position i holds the piece from position p(i), which picked up the
+// orientation o(i) on the way, and doing one sequence after another adds up the orientations
+// along the path each piece takes. On a supercube the quarter turns of the centres add up the same
+// way; combining a supercube state with one that isn't leaves the centres' turns unknown.
+impl NormalizedSkewb {
+    // The state reached by doing what other does after what self does.
+    pub fn compose(&self, other: &NormalizedSkewb) -> NormalizedSkewb {
+        let solved = NormalizedSkewb::new();
+        let mut composed = NormalizedSkewb::new();
+        let mut orientations = [0; 6];
+        for i in 0..6 {
+            let from = solved
+                .center_pieces
+                .iter()
+                .position(|&c| c == other.center_pieces[i])
+                .unwrap();
+            composed.center_pieces[i] = self.center_pieces[from];
+            if let (Some(a), Some(b)) = (self.center_orientations, other.center_orientations) {
+                orientations[i] = (a[from] + b[i]) % 4;
+            }
+        }
+        if self.center_orientations.is_some() && other.center_orientations.is_some() {
+            composed.center_orientations = Some(orientations);
+        }
+        for i in 0..4 {
+            let from = other.floating_pieces[i];
+            composed.floating_pieces[i] = self.floating_pieces[from];
+            composed.floating_orientations[i] =
+                self.floating_orientations[from] + other.floating_orientations[i];
+            composed.fixed_orientations[i] =
+                self.fixed_orientations[i] + other.fixed_orientations[i];
+        }
+        composed
+    }
+
+    // The state that undoes what self does.
+    pub fn inverse(&self) -> NormalizedSkewb {
+        let solved = NormalizedSkewb::new();
+        let mut inverse = NormalizedSkewb::new();
+        let mut orientations = [0; 6];
+        for i in 0..6 {
+            let from = solved
+                .center_pieces
+                .iter()
+                .position(|&c| c == self.center_pieces[i])
+                .unwrap();
+            inverse.center_pieces[from] = solved.center_pieces[i];
+            if let Some(o) = self.center_orientations {
+                orientations[from] = (4 - o[i]) % 4;
+            }
+        }
+        inverse.center_orientations = self.center_orientations.map(|_| orientations);
+        for i in 0..4 {
+            let from = self.floating_pieces[i];
+            inverse.floating_pieces[from] = i;
+            inverse.floating_orientations[from] = Orientation::UD - self.floating_orientations[i];
+            inverse.fixed_orientations[i] = Orientation::UD - self.fixed_orientations[i];
+        }
+        inverse
+    }
+
+    // by, then self, then by undone: [C: X] for moves C reaching by and X reaching self.
+    pub fn conjugate(&self, by: &NormalizedSkewb) -> NormalizedSkewb {
+        by.compose(self).compose(&by.inverse())
+    }
+}
+
+#[test]
+fn agrees_with_moves() {
+    use skewb::Move;
+    use solver::scramble_moves;
+
+    let inverse = |moves: &[Move]| -> Vec<Move> { moves.iter().rev().map(Move::inverse).collect() };
+    for seed in 0..20 {
+        let x = scramble_moves(seed, 9);
+        let y = scramble_moves(seed + 100, 6);
+        let (a, b) = (NormalizedSkewb::after(&x), NormalizedSkewb::after(&y));
+
+        let mut both = x.clone();
+        both.extend_from_slice(&y);
+        assert_eq!(NormalizedSkewb::after(&both), a.compose(&b));
+        assert_eq!(NormalizedSkewb::after(&inverse(&x)), a.inverse());
+        assert!(a.compose(&a.inverse()).is_solved());
+        assert!(a.inverse().compose(&a).is_solved());
+
+        let mut conjugated = y.clone();
+        conjugated.extend_from_slice(&x);
+        conjugated.extend(inverse(&y));
+        assert_eq!(NormalizedSkewb::after(&conjugated), a.conjugate(&b));
+    }
+}
+
+#[test]
+fn identity_and_associativity() {
+    use solver::scramble;
+
+    let solved = NormalizedSkewb::new();
+    let (a, b, c) = (scramble(1, 20), scramble(2, 20), scramble(3, 20));
+    assert_eq!(a, a.compose(&solved));
+    assert_eq!(a, solved.compose(&a));
+    assert_eq!(a.compose(&b).compose(&c), a.compose(&b.compose(&c)));
+    assert_eq!(b.inverse().compose(&a.inverse()), a.compose(&b).inverse());
+    assert_eq!(a, a.conjugate(&solved));
+}
+
+#[test]
+fn supercube_centers() {
+    use solver::scramble_moves;
+
+    let supercube = NormalizedSkewb::supercube();
+    for seed in 0..10 {
+        let x = scramble_moves(seed, 9);
+        let y = scramble_moves(seed + 100, 6);
+        let (a, b) = (supercube.applied(&x), supercube.applied(&y));
+        assert_eq!(supercube.applied(&[x.clone(), y].concat()), a.compose(&b));
+        assert!(a.compose(&a.inverse()).is_solved());
+        assert_eq!(supercube, a.inverse().compose(&a));
+    }
+
+    // Four centres turned half way round, with every piece back in place.
+    let mut twisted = NormalizedSkewb::supercube();
+    twisted.center_orientations = Some([2, 2, 2, 2, 0, 0]);
+    assert!(!twisted.is_solved());
+    assert_eq!(twisted, twisted.inverse());
+    assert!(twisted.compose(&twisted).is_solved());
+    assert_eq!(
+        None,
+        twisted.compose(&NormalizedSkewb::new()).center_orientations
+    );
+}
@@ -0,0 +1,139 @@
+use skewb::{Move, NormalizedSkewb};
+use solver::{IdaStar, SearchConfig, SearchError, Solver};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    Progress,
+    Neutral,
+    Worse,
+}
+
+// What one move from a state does to its distance from solved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hint {
+    pub move_: Move,
+    // The optimal distance from solved after the move.
+    pub distance: usize,
+    pub verdict: Verdict,
+}
+
+impl NormalizedSkewb {
+    // Every move from this state and whether it gets closer to solved.
+    pub fn hints(&self) -> Result<Vec<Hint>, SearchError> {
+        self.hints_with(IdaStar::shared(), &SearchConfig::default())
+    }
+
+    // The same as hints, but with any solver and only the moves and limits the config allows. If
+    // a distance can't be found, the error is the solver's.
+    pub fn hints_with<S: Solver + ?Sized>(
+        &self,
+        solver: &S,
+        config: &SearchConfig,
+    ) -> Result<Vec<Hint>, SearchError> {
+        let distance =
+            |state: &NormalizedSkewb| solver.solve(state, config).map(|solution| solution.len());
+
+        let current = distance(self)?;
+        self.neighbors()
+            .into_iter()
+            .filter(|(move_, _)| config.moves.contains(move_))
+            .map(|(move_, next)| {
+                let d = distance(&next)?;
+                let verdict = if d < current {
+                    Verdict::Progress
+                } else if d == current {
+                    Verdict::Neutral
+                } else {
+                    Verdict::Worse
+                };
+                Ok(Hint {
+                    move_,
+                    distance: d,
+                    verdict,
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn hints_two_moves_from_solved() {
+    use skewb::Direction;
+
+    let mut skewb = NormalizedSkewb::new();
+    skewb.turn_lr((0, 0, 0));
+    skewb.turn_lr((1, 0, 1));
+    let hints = skewb.hints().unwrap();
+
+    assert_eq!(8, hints.len());
+    let progress: Vec<Hint> = hints
+        .iter()
+        .cloned()
+        .filter(|h| h.verdict == Verdict::Progress)
+        .collect();
+    assert_eq!(
+        vec![Hint {
+            move_: Move::new((1, 0, 1), Direction::FB),
+            distance: 1,
+            verdict: Verdict::Progress,
+        }],
+        progress
+    );
+    for hint in hints.iter().filter(|h| h.verdict != Verdict::Progress) {
+        assert!(hint.distance >= 2);
+    }
+}
+
+#[test]
+fn hints_agree_with_the_table() {
+    use solver::{scramble, TableLookup};
+
+    let lookup = TableLookup::new();
+    let skewb = scramble(5, 30);
+    let current = lookup.table().distance(&skewb).unwrap();
+    let hints = skewb.hints_with(&lookup, &SearchConfig::default()).unwrap();
+    assert_eq!(Ok(hints.clone()), skewb.hints());
+    for hint in hints.iter() {
+        let mut next = skewb.clone();
+        next.do_move(&hint.move_);
+        assert_eq!(lookup.table().distance(&next), Some(hint.distance));
+        assert_eq!(hint.verdict == Verdict::Progress, hint.distance < current);
+    }
+    assert!(hints.iter().any(|h| h.verdict == Verdict::Progress));
+}
+
+#[test]
+fn limited_hints() {
+    use skewb::{Direction, MoveSet};
+    use solver::scramble;
+
+    let skewb = scramble(6, 30);
+    let config = SearchConfig {
+        max_depth: 2,
+        ..SearchConfig::default()
+    };
+    assert_eq!(
+        Err(SearchError::NotFound),
+        skewb.hints_with(IdaStar::shared(), &config)
+    );
+
+    let mut skewb = NormalizedSkewb::new();
+    skewb.turn_lr((0, 0, 0));
+    let config = SearchConfig {
+        moves: MoveSet::new(&[(0, 0, 0)], None),
+        ..SearchConfig::default()
+    };
+    let hints = skewb.hints_with(IdaStar::shared(), &config).unwrap();
+    assert_eq!(
+        vec![
+            Move::new((0, 0, 0), Direction::FB),
+            Move::new((0, 0, 0), Direction::LR)
+        ],
+        hints.iter().map(|h| h.move_).collect::<Vec<_>>()
+    );
+    skewb.turn_lr((0, 1, 1));
+    assert_eq!(
+        Err(SearchError::OutsideSubgroup),
+        skewb.hints_with(IdaStar::shared(), &config)
+    );
+}
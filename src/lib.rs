@@ -3,6 +3,24 @@ extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
 
+pub mod algorithm;
+pub mod analysis;
+pub mod batch;
+pub mod commutators;
+pub mod depth;
+pub mod diamond;
 pub mod drawer;
+pub mod ergonomics;
+pub mod group;
+pub mod hints;
+pub mod notation;
+pub mod puzzle;
+pub mod rotation;
+pub mod schreier_sims;
 pub mod skewb;
+pub mod solver;
+pub mod symmetry;
+pub mod table;
+pub mod trace;
 mod unordered_pair;
+pub mod wca;
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rotation::Frame;
+use skewb::{Corner, Direction, Move, FIXED_CORNERS};
+
+// The corners WCA notation turns, with the puzzle held still. B is a floating corner; the other
+// three are fixed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WcaCorner {
+    R,
+    U,
+    L,
+    B,
+}
+
+impl WcaCorner {
+    pub fn all() -> [WcaCorner; 4] { [WcaCorner::R, WcaCorner::U, WcaCorner::L, WcaCorner::B] }
+
+    pub fn corner(self) -> Corner {
+        match self {
+            WcaCorner::R => (1, 1, 0),
+            WcaCorner::U => (0, 0, 0),
+            WcaCorner::L => (1, 0, 1),
+            WcaCorner::B => (1, 0, 0),
+        }
+    }
+}
+
+// A WCA move: a clockwise turn of one of the WCA corners looking at it, or anticlockwise if
+// primed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WcaMove {
+    pub corner: WcaCorner,
+    pub prime: bool,
+}
+
+impl WcaMove {
+    pub fn new(corner: WcaCorner, prime: bool) -> WcaMove { WcaMove { corner, prime } }
+
+    pub fn inverse(&self) -> WcaMove { WcaMove::new(self.corner, !self.prime) }
+
+    // Every WCA move, in the order R R' U U' L L' B B'.
+    pub fn all() -> Vec<WcaMove> {
+        let mut moves = vec![];
+        for &corner in WcaCorner::all().iter() {
+            moves.push(WcaMove::new(corner, false));
+            moves.push(WcaMove::new(corner, true));
+        }
+        moves
+    }
+
+    // The same turn as a Move of a Skewb held still. turn_lr is clockwise from a fixed corner but
+    // anticlockwise from a floating one.
+    pub fn to_move(&self) -> Move {
+        let c = self.corner.corner();
+        let clockwise = if FIXED_CORNERS.contains(&c) {
+            Direction::LR
+        } else {
+            Direction::FB
+        };
+        let direction = if self.prime {
+            clockwise.inverse()
+        } else {
+            clockwise
+        };
+        Move::new(c, direction)
+    }
+
+    // The WCA move that is the same turn, if the move is around one of the WCA corners.
+    pub fn from_move(move_: &Move) -> Option<WcaMove> {
+        WcaMove::all()
+            .into_iter()
+            .find(|wca| wca.to_move() == *move_)
+    }
+}
+
+impl fmt::Display for WcaMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.corner)?;
+        if self.prime {
+            write!(f, "'")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for WcaMove {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<WcaMove, String> {
+        let corner = match text.trim_end_matches('\'') {
+            "R" => WcaCorner::R,
+            "U" => WcaCorner::U,
+            "L" => WcaCorner::L,
+            "B" => WcaCorner::B,
+            _ => return Err(format!("{} is not a WCA move", text)),
+        };
+        match text.len() - text.trim_end_matches('\'').len() {
+            0 => Ok(WcaMove::new(corner, false)),
+            1 => Ok(WcaMove::new(corner, true)),
+            _ => Err(format!("{} is not a WCA move", text)),
+        }
+    }
+}
+
+// Moves separated by spaces, like "R U' L".
+pub fn parse_wca(text: &str) -> Result<Vec<WcaMove>, String> {
+    text.split_whitespace().map(str::parse).collect()
+}
+
+pub fn format_wca(moves: &[WcaMove]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn opposite(c: Corner) -> Corner { (1 - c.0, 1 - c.1, 1 - c.2) }
+
+// Turning a corner one way and turning the opposite corner the other way only differ by turning
+// the whole puzzle around the diagonal through them. This is how many times, in the sense of
+// around_corner, the turn of c is ahead of the turn of its opposite.
+fn turns_ahead(direction: Direction) -> u8 {
+    match direction {
+        Direction::LR => 1,
+        Direction::FB => 2,
+    }
+}
+
+// WCA moves that do the same as the fixed-corner moves do to a NormalizedSkewb, without any
+// rotations in between. Every turn the WCA corners can't make is made by turning the opposite
+// corner instead, which leaves the puzzle held a different way for the moves after it.
+pub fn to_wca(moves: &[Move]) -> Vec<WcaMove> { to_wca_from(&Frame::identity(), moves) }
+
+// The same as to_wca, for a puzzle held with the corners of the normalized puzzle carried by the
+// frame, such as from_wca leaves it.
+pub fn to_wca_from(frame: &Frame, moves: &[Move]) -> Vec<WcaMove> {
+    // Where the corners of the normalized puzzle are on the puzzle being held.
+    let mut frame = *frame;
+    let mut wca = vec![];
+    for move_ in moves.iter() {
+        let turn = frame.carry(move_);
+        match WcaMove::from_move(&turn) {
+            Some(w) => wca.push(w),
+            None => {
+                let instead = Move::new(opposite(turn.corner()), turn.direction().inverse());
+                wca.push(WcaMove::from_move(&instead).expect("opposite of a WCA corner"));
+                let behind = 3 - turns_ahead(turn.direction());
+                frame = frame.then(&Frame::around_corner(turn.corner(), behind));
+            }
+        }
+    }
+    wca
+}
+
+// The fixed-corner moves that do the same as the WCA moves, followed by where the moves leave
+// the corners of the normalized puzzle on the puzzle being held.
+pub fn from_wca(moves: &[WcaMove]) -> (Vec<Move>, Frame) {
+    let turns: Vec<Move> = moves.iter().map(WcaMove::to_move).collect();
+    fixed_corner_form(&turns)
+}
+
+// The fixed-corner moves that do the same as turns of any corners of a puzzle held still,
+// followed by where the turns leave the corners of the normalized puzzle on the puzzle being
+// held. A turn of a floating corner is made by turning the opposite fixed corner instead.
+pub fn fixed_corner_form(turns: &[Move]) -> (Vec<Move>, Frame) {
+    let mut frame = Frame::identity();
+    let mut fixed = vec![];
+    for turn in turns.iter() {
+        let turn = frame.unrotate(turn);
+        if FIXED_CORNERS.contains(&turn.corner()) {
+            fixed.push(turn);
+        } else {
+            fixed.push(Move::new(
+                opposite(turn.corner()),
+                turn.direction().inverse(),
+            ));
+            let ahead = Frame::around_corner(turn.corner(), turns_ahead(turn.direction()));
+            frame = ahead.then(&frame);
+        }
+    }
+    (fixed, frame)
+}
+
+#[test]
+fn wca_moves() {
+    let moves = WcaMove::all();
+    assert_eq!("R R' U U' L L' B B'", format_wca(&moves));
+    assert_eq!(Ok(moves.clone()), parse_wca(" R R' U  U' L L' B B'"));
+    assert!(parse_wca("R F").is_err());
+    assert!(parse_wca("R''").is_err());
+    for wca in moves.iter() {
+        assert_eq!(Some(*wca), WcaMove::from_move(&wca.to_move()));
+        assert_eq!(wca.inverse().to_move(), wca.to_move().inverse());
+    }
+    assert_eq!(
+        None,
+        WcaMove::from_move(&Move::new((0, 1, 1), Direction::LR))
+    );
+}
+
+#[test]
+fn translating_solutions() {
+    use skewb::{NormalizedSkewb, Skewb};
+    use solver::scramble_moves;
+
+    for seed in 0..20 {
+        let moves = scramble_moves(seed, 12);
+        let wca = to_wca(&moves);
+        assert_eq!(moves.len(), wca.len());
+        let (fixed, frame) = from_wca(&wca);
+        assert_eq!(moves, fixed);
+
+        // The WCA moves leave the held puzzle in the same state as the fixed-corner moves, up to
+        // the rotation from_wca reports.
+        let mut held = Skewb::new();
+        for w in wca.iter() {
+            held.do_move(&w.to_move());
+        }
+        let mut expected = NormalizedSkewb::after(&moves).denormalize();
+        for &rotation in frame.rotations().iter() {
+            expected.rotate(rotation);
+        }
+        assert_eq!(expected, held);
+    }
+}
+
+#[test]
+fn back_from_wca() {
+    use skewb::{NormalizedSkewb, Skewb};
+
+    let b = WcaMove::new(WcaCorner::B, false);
+    let r = WcaMove::new(WcaCorner::R, true);
+    let (moves, frame) = from_wca(&[b, r, b]);
+    assert_eq!(Move::new((0, 1, 1), Direction::LR), moves[0]);
+    assert!(!frame.is_identity());
+
+    let mut held = Skewb::new();
+    for w in [b, r, b].iter() {
+        held.do_move(&w.to_move());
+    }
+    let mut expected = NormalizedSkewb::after(&moves).denormalize();
+    for &rotation in frame.rotations().iter() {
+        expected.rotate(rotation);
+    }
+    assert_eq!(expected, held);
+    assert_eq!(moves, from_wca(&to_wca(&moves)).0);
+}
@@ -0,0 +1,102 @@
+use std::hash::Hash;
+
+use skewb::{Move, NormalizedSkewb};
+
+// What a search needs to know about a puzzle: its states, the moves that can be made from any of
+// them, and how to tell when it's solved. Iddfs searches any puzzle like this.
+pub trait TwistyPuzzle: Clone + Eq + Hash {
+    type Move: Copy + Eq;
+
+    // Every move of the puzzle.
+    fn moves() -> Vec<Self::Move>;
+    fn do_move(&mut self, move_: &Self::Move);
+    fn undo_move(&mut self, move_: &Self::Move);
+    fn is_solved(&self) -> bool;
+
+    // Whether making the move right after the moves so far can't be part of a shortest solution
+    // using only the given moves, so that searches can skip it. Searches still work if it always
+    // says no, just more slowly.
+    fn redundant(_move_stack: &[Self::Move], _move_: &Self::Move, _moves: &[Self::Move]) -> bool {
+        false
+    }
+}
+
+impl TwistyPuzzle for NormalizedSkewb {
+    type Move = Move;
+
+    fn moves() -> Vec<Move> { Move::all() }
+    fn do_move(&mut self, move_: &Move) { NormalizedSkewb::do_move(self, move_) }
+    fn undo_move(&mut self, move_: &Move) { NormalizedSkewb::undo_move(self, move_) }
+    fn is_solved(&self) -> bool { NormalizedSkewb::is_solved(self) }
+
+    // Never turn the same corner twice in a row, since two turns of one corner are one turn the
+    // other way, unless that way isn't among the moves.
+    fn redundant(move_stack: &[Move], move_: &Move, moves: &[Move]) -> bool {
+        let directions = moves
+            .iter()
+            .filter(|m| m.corner() == move_.corner())
+            .count();
+        let repeats = if directions == 1 { 2 } else { 1 };
+        move_stack.len() >= repeats
+            && move_stack[move_stack.len() - repeats..]
+                .iter()
+                .all(|m| m.corner() == move_.corner())
+    }
+}
+
+// A dial with five positions that turns one step either way, which is all a search needs to
+// find its way back to zero.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Dial(i8);
+
+#[cfg(test)]
+impl TwistyPuzzle for Dial {
+    type Move = i8;
+
+    fn moves() -> Vec<i8> { vec![1, -1] }
+    fn do_move(&mut self, move_: &i8) { self.0 = (self.0 + move_).rem_euclid(5) }
+    fn undo_move(&mut self, move_: &i8) { self.0 = (self.0 - move_).rem_euclid(5) }
+    fn is_solved(&self) -> bool { self.0 == 0 }
+}
+
+#[test]
+fn any_puzzle() {
+    use solver::{Iddfs, SearchConfig};
+
+    let config = SearchConfig::default();
+    let moves = Dial::moves();
+    assert_eq!(
+        Ok(vec![-1, -1]),
+        Iddfs.solve_puzzle(&Dial(2), &Dial(0), &moves, &config)
+    );
+    assert_eq!(
+        Ok(vec![1, 1]),
+        Iddfs.solve_puzzle(&Dial(3), &Dial(0), &moves, &config)
+    );
+    assert_eq!(
+        Ok(vec![]),
+        Iddfs.solve_puzzle(&Dial(4), &Dial(4), &moves, &config)
+    );
+    assert_eq!(Ok(vec![-1, -1]), Iddfs.solve_any(&Dial(2), &config));
+    assert_eq!(Ok(vec![]), Iddfs.solve_any(&Dial(0), &config));
+}
+
+#[test]
+fn skewbs_are_puzzles() {
+    use solver::{scramble, Iddfs, SearchConfig, Solver};
+
+    let config = SearchConfig::default();
+    let start = scramble(3, 5);
+    let moves = <NormalizedSkewb as TwistyPuzzle>::moves();
+    let solution = Iddfs
+        .solve_puzzle(&start, &NormalizedSkewb::new(), &moves, &config)
+        .unwrap();
+    assert_eq!(Iddfs.solve(&start, &config), Ok(solution.clone()));
+    assert_eq!(Iddfs.solve_any(&start, &config), Ok(solution.clone()));
+    let mut state = start;
+    for move_ in solution.iter() {
+        TwistyPuzzle::do_move(&mut state, move_);
+    }
+    assert!(TwistyPuzzle::is_solved(&state));
+}
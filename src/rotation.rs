@@ -0,0 +1,475 @@
+use std::fmt;
+use std::str::FromStr;
+
+use notation::WcaMove;
+use skewb::{corner_name, Corner, Direction, Move, Skewb, FIXED_CORNERS, FLOATING_CORNERS};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+// A rotation of the whole puzzle by some clockwise quarter turns, looking at the R face for x, the
+// U face for y and the F face for z. Two quarter turns make x2, three make x'.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    axis: Axis,
+    quarter_turns: u8,
+}
+
+impl Rotation {
+    pub fn new(axis: Axis, quarter_turns: u8) -> Rotation {
+        Rotation {
+            axis,
+            quarter_turns: quarter_turns % 4,
+        }
+    }
+    pub fn axis(&self) -> Axis { self.axis }
+    pub fn quarter_turns(&self) -> u8 { self.quarter_turns }
+    pub fn inverse(&self) -> Rotation { Rotation::new(self.axis, 4 - self.quarter_turns) }
+
+    // Every rotation that changes which way round the puzzle is held.
+    pub fn all() -> Vec<Rotation> {
+        let mut rotations = vec![];
+        for &axis in [Axis::X, Axis::Y, Axis::Z].iter() {
+            for quarter_turns in 1..4 {
+                rotations.push(Rotation::new(axis, quarter_turns));
+            }
+        }
+        rotations
+    }
+}
+
+// How a number of quarter turns is written after the axis.
+const TURN_SUFFIXES: [&str; 4] = ["0", "", "2", "'"];
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let axis = match self.axis {
+            Axis::X => "x",
+            Axis::Y => "y",
+            Axis::Z => "z",
+        };
+        write!(f, "{}{}", axis, TURN_SUFFIXES[self.quarter_turns as usize])
+    }
+}
+
+// Like x, y2 or z'.
+impl FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Rotation, String> {
+        let axis = match text.chars().next() {
+            Some('x') => Axis::X,
+            Some('y') => Axis::Y,
+            Some('z') => Axis::Z,
+            _ => return Err(format!("{} is not a rotation", text)),
+        };
+        TURN_SUFFIXES
+            .iter()
+            .position(|&suffix| suffix == &text[1..])
+            .map(|quarter_turns| Rotation::new(axis, quarter_turns as u8))
+            .ok_or_else(|| format!("{} is not a rotation", text))
+    }
+}
+
+// Which way round the puzzle is held after some rotations, as where each corner position has been
+// carried to. The corner at c ends up at the position whose a'th coordinate is
+// c[axes[a]] ^ flips[a].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Frame {
+    axes: [usize; 3],
+    flips: [u8; 3],
+}
+
+impl Default for Frame {
+    fn default() -> Frame { Frame::identity() }
+}
+
+impl Frame {
+    pub fn identity() -> Frame {
+        Frame {
+            axes: [0, 1, 2],
+            flips: [0, 0, 0],
+        }
+    }
+
+    // Where a single quarter turn about each axis carries the corners.
+    fn quarter_turn(axis: Axis) -> Frame {
+        match axis {
+            Axis::X => Frame {
+                axes: [2, 1, 0],
+                flips: [1, 0, 0],
+            },
+            Axis::Y => Frame {
+                axes: [0, 2, 1],
+                flips: [0, 1, 0],
+            },
+            Axis::Z => Frame {
+                axes: [1, 0, 2],
+                flips: [0, 1, 0],
+            },
+        }
+    }
+
+    // Where turning the whole puzzle around the diagonal through c carries the corners, turning
+    // it the way turn_lr turns the corners around c, the given number of times.
+    pub(crate) fn around_corner(c: Corner, turns: u8) -> Frame {
+        let c = [c.0, c.1, c.2];
+        let axes = [2, 0, 1];
+        let once = Frame {
+            axes,
+            flips: [c[axes[0]] ^ c[0], c[axes[1]] ^ c[1], c[axes[2]] ^ c[2]],
+        };
+        let mut frame = Frame::identity();
+        for _ in 0..turns {
+            frame = frame.then(&once);
+        }
+        frame
+    }
+
+    pub fn is_identity(&self) -> bool { *self == Frame::identity() }
+
+    // The 24 ways of holding the puzzle, starting with the identity.
+    pub fn all() -> Vec<Frame> {
+        let mut frames = vec![Frame::identity()];
+        let mut i = 0;
+        while i < frames.len() {
+            for &rotation in Rotation::all().iter() {
+                let frame = frames[i].rotate(rotation);
+                if !frames.contains(&frame) {
+                    frames.push(frame);
+                }
+            }
+            i += 1;
+        }
+        frames
+    }
+
+    // The position the corner at c has been carried to.
+    pub fn corner(&self, c: Corner) -> Corner {
+        let c = [c.0, c.1, c.2];
+        (
+            c[self.axes[0]] ^ self.flips[0],
+            c[self.axes[1]] ^ self.flips[1],
+            c[self.axes[2]] ^ self.flips[2],
+        )
+    }
+
+    // This frame followed by the other one.
+    pub fn then(&self, other: &Frame) -> Frame {
+        let mut frame = Frame::identity();
+        for a in 0..3 {
+            frame.axes[a] = self.axes[other.axes[a]];
+            frame.flips[a] = self.flips[other.axes[a]] ^ other.flips[a];
+        }
+        frame
+    }
+
+    pub fn rotate(&self, rotation: Rotation) -> Frame {
+        let mut frame = *self;
+        for _ in 0..rotation.quarter_turns {
+            frame = frame.then(&Frame::quarter_turn(rotation.axis));
+        }
+        frame
+    }
+
+    pub fn inverse(&self) -> Frame {
+        let mut frame = Frame::identity();
+        for a in 0..3 {
+            frame.axes[self.axes[a]] = a;
+            frame.flips[self.axes[a]] = self.flips[a];
+        }
+        frame
+    }
+
+    // Whether the frame puts floating corners where the fixed ones were. Every quarter turn does.
+    pub fn swaps_fixed_and_floating(&self) -> bool {
+        let (i, j, k) = self.corner((0, 0, 0));
+        (i + j + k) % 2 == 1
+    }
+
+    // A shortest list of rotations that turns the puzzle from the identity to this frame.
+    pub fn rotations(&self) -> Vec<Rotation> {
+        let mut paths = vec![(Frame::identity(), vec![])];
+        let mut i = 0;
+        while paths[i].0 != *self {
+            for &rotation in Rotation::all().iter() {
+                let frame = paths[i].0.rotate(rotation);
+                if paths.iter().all(|(f, _)| *f != frame) {
+                    let mut path = paths[i].1.clone();
+                    path.push(rotation);
+                    paths.push((frame, path));
+                }
+            }
+            i += 1;
+        }
+        paths.swap_remove(i).1
+    }
+
+    // The same turn, made after the frame has carried the puzzle. Turning a corner the same way
+    // always looks clockwise from fixed corners and anticlockwise from floating ones, so the
+    // direction flips along with them.
+    pub fn carry(&self, move_: &Move) -> Move {
+        let direction = if self.swaps_fixed_and_floating() {
+            move_.direction().inverse()
+        } else {
+            move_.direction()
+        };
+        Move::new(self.corner(move_.corner()), direction)
+    }
+
+    // A move of the corner now at the position the given move names, the way the puzzle was held
+    // before.
+    pub fn unrotate(&self, move_: &Move) -> Move { self.inverse().carry(move_) }
+}
+
+// One step of an algorithm as it is written down: either a turn of whichever corner is at a
+// position, or a rotation of the whole puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    Turn(Move),
+    Rotate(Rotation),
+}
+
+// Turns are written like WCA moves, clockwise looking at the corner unless primed, so that a
+// sequence reads like "x R y' U". The WCA letters only name four of the corners, so turns of the
+// others are written with the faces the corner is on, like UFR'.
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Step::Rotate(rotation) => write!(f, "{}", rotation),
+            Step::Turn(move_) => match WcaMove::from_move(&move_) {
+                Some(wca) => write!(f, "{}", wca),
+                None => {
+                    write!(f, "{}", corner_name(move_.corner()))?;
+                    if move_ != clockwise(move_.corner()) {
+                        write!(f, "'")?;
+                    }
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Step, String> {
+        if let Ok(wca) = text.parse::<WcaMove>() {
+            return Ok(Step::Turn(wca.to_move()));
+        }
+        if let Ok(rotation) = text.parse() {
+            return Ok(Step::Rotate(rotation));
+        }
+        let name = text.strip_suffix('\'').unwrap_or(text);
+        let corner = FIXED_CORNERS
+            .iter()
+            .chain(FLOATING_CORNERS.iter())
+            .find(|&&c| corner_name(c) == name)
+            .ok_or_else(|| format!("{} is not a turn or a rotation", text))?;
+        let turn = clockwise(*corner);
+        if name.len() < text.len() {
+            Ok(Step::Turn(turn.inverse()))
+        } else {
+            Ok(Step::Turn(turn))
+        }
+    }
+}
+
+// The turn of the corner that's clockwise looking at it. turn_lr is clockwise from a fixed corner
+// but anticlockwise from a floating one.
+fn clockwise(c: Corner) -> Move {
+    if FIXED_CORNERS.contains(&c) {
+        Move::new(c, Direction::LR)
+    } else {
+        Move::new(c, Direction::FB)
+    }
+}
+
+// Steps separated by spaces, like "x R y' U".
+pub fn parse_steps(text: &str) -> Result<Vec<Step>, String> {
+    text.split_whitespace().map(str::parse).collect()
+}
+
+pub fn format_steps(steps: &[Step]) -> String {
+    steps
+        .iter()
+        .map(|step| step.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The turns that do the same as the steps while the puzzle is held the same way throughout,
+// followed by which way round the steps leave it.
+pub fn strip_rotations(steps: &[Step]) -> (Vec<Move>, Frame) {
+    let mut frame = Frame::identity();
+    let mut moves = vec![];
+    for step in steps.iter() {
+        match *step {
+            Step::Turn(move_) => moves.push(frame.unrotate(&move_)),
+            Step::Rotate(rotation) => frame = frame.rotate(rotation),
+        }
+    }
+    (moves, frame)
+}
+
+impl Skewb {
+    pub fn rotate(&mut self, rotation: Rotation) {
+        for _ in 0..rotation.quarter_turns() {
+            match rotation.axis() {
+                Axis::X => self.rotate_lr(),
+                Axis::Y => self.rotate_ud(),
+                Axis::Z => self.rotate_fb(),
+            }
+        }
+    }
+
+    pub fn do_step(&mut self, step: &Step) {
+        match *step {
+            Step::Turn(move_) => self.do_move(&move_),
+            Step::Rotate(rotation) => self.rotate(rotation),
+        }
+    }
+
+    // Does every step and returns which way round they leave the puzzle.
+    pub fn do_steps(&mut self, steps: &[Step]) -> Frame {
+        for step in steps.iter() {
+            self.do_step(step);
+        }
+        strip_rotations(steps).1
+    }
+}
+
+#[test]
+fn rotations_carry_corners() {
+    let y = Frame::identity().rotate(Rotation::new(Axis::Y, 1));
+    assert_eq!((0, 0, 0), y.corner((0, 0, 1)));
+    let x = Frame::identity().rotate(Rotation::new(Axis::X, 1));
+    assert_eq!((0, 0, 0), x.corner((0, 0, 1)));
+    let z = Frame::identity().rotate(Rotation::new(Axis::Z, 1));
+    assert_eq!((0, 1, 0), z.corner((0, 0, 0)));
+
+    for &rotation in Rotation::all().iter() {
+        let frame = y.rotate(rotation);
+        assert_eq!(y, frame.rotate(rotation.inverse()));
+        assert!(frame.then(&frame.inverse()).is_identity());
+        assert_eq!(
+            rotation.quarter_turns() % 2 == 1,
+            Frame::identity()
+                .rotate(rotation)
+                .swaps_fixed_and_floating()
+        );
+    }
+    let frames = Frame::all();
+    assert_eq!(24, frames.len());
+    assert!(frames[0].is_identity());
+    for frame in frames.iter() {
+        let rotations = frame.rotations();
+        assert!(rotations.len() <= 2);
+        let rotated = rotations
+            .iter()
+            .fold(Frame::identity(), |f, &r| f.rotate(r));
+        assert_eq!(*frame, rotated);
+    }
+}
+
+#[test]
+fn stripping_rotations() {
+    let turn = |corner, direction| Step::Turn(Move::new(corner, direction));
+    let rotate = |axis, quarter_turns| Step::Rotate(Rotation::new(axis, quarter_turns));
+    let steps = [
+        turn((0, 0, 0), Direction::LR),
+        rotate(Axis::X, 1),
+        turn((1, 1, 0), Direction::FB),
+        turn((0, 0, 1), Direction::LR),
+        rotate(Axis::Y, 2),
+        turn((0, 1, 1), Direction::LR),
+        rotate(Axis::Z, 3),
+        turn((1, 0, 0), Direction::FB),
+        rotate(Axis::Y, 1),
+        turn((1, 1, 1), Direction::LR),
+    ];
+
+    let mut written = Skewb::new();
+    let frame = written.do_steps(&steps);
+    let (moves, stripped_frame) = strip_rotations(&steps);
+    assert_eq!(frame, stripped_frame);
+    assert_eq!(6, moves.len());
+    assert_eq!(Move::new((0, 0, 0), Direction::LR), moves[0]);
+
+    let mut stripped = Skewb::new();
+    for move_ in moves.iter() {
+        stripped.do_move(move_);
+    }
+    for &rotation in frame.rotations().iter() {
+        stripped.rotate(rotation);
+    }
+    assert_eq!(written, stripped);
+}
+
+#[test]
+fn turning_the_opposite_corner() {
+    for i in 0..2 {
+        for j in 0..2 {
+            for k in 0..2 {
+                let c = (i, j, k);
+                let opposite = (1 - i, 1 - j, 1 - k);
+                for &(direction, turns) in [(Direction::LR, 1), (Direction::FB, 2)].iter() {
+                    let mut turned = Skewb::supercube();
+                    turned.turn_lr((0, 0, 0));
+                    let mut expected = turned.clone();
+                    expected.do_move(&Move::new(c, direction));
+                    turned.do_move(&Move::new(opposite, direction.inverse()));
+                    for &rotation in Frame::around_corner(c, turns).rotations().iter() {
+                        turned.rotate(rotation);
+                    }
+                    assert_eq!(expected, turned);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn writing_steps() {
+    let steps = parse_steps("x R y' U  UFR' z2 DBL").unwrap();
+    assert_eq!(
+        vec![
+            Step::Rotate(Rotation::new(Axis::X, 1)),
+            Step::Turn(Move::new((1, 1, 0), Direction::LR)),
+            Step::Rotate(Rotation::new(Axis::Y, 3)),
+            Step::Turn(Move::new((0, 0, 0), Direction::LR)),
+            Step::Turn(Move::new((0, 1, 1), Direction::FB)),
+            Step::Rotate(Rotation::new(Axis::Z, 2)),
+            Step::Turn(Move::new((1, 0, 0), Direction::FB)),
+        ],
+        steps
+    );
+    // B is the WCA name for DBL.
+    assert_eq!("x R y' U UFR' z2 B", format_steps(&steps));
+    for &rotation in Rotation::all().iter() {
+        assert_eq!(Ok(rotation), rotation.to_string().parse());
+    }
+    for move_ in FIXED_CORNERS
+        .iter()
+        .chain(FLOATING_CORNERS.iter())
+        .flat_map(|&c| [Move::new(c, Direction::LR), Move::new(c, Direction::FB)])
+    {
+        assert_eq!(Ok(Step::Turn(move_)), Step::Turn(move_).to_string().parse());
+    }
+
+    let mut written = Skewb::new();
+    written.do_steps(&steps);
+    let mut parsed = Skewb::new();
+    parsed.do_steps(&parse_steps(&format_steps(&steps)).unwrap());
+    assert_eq!(written, parsed);
+
+    assert!(parse_steps("x R w").is_err());
+    assert!(parse_steps("x3").is_err());
+    assert!(parse_steps("UFR''").is_err());
+    assert!(parse_steps("FUR").is_err());
+}
@@ -0,0 +1,280 @@
+use skewb::{orientation_to_i, Move, NormalizedSkewb};
+
+// A state as a permutation of 48 points: each centre in each of its four quarter turns, then each
+// floating corner piece in each of its three orientations, then each fixed corner in each of its
+// orientations. A centre that isn't on a supercube counts as unturned. perm[x] is where the state
+// takes point x, so permutations compose left to right like moves.
+const POINTS: usize = 48;
+type Perm = [u8; POINTS];
+
+const IDENTITY: Perm = {
+    let mut perm = [0; POINTS];
+    let mut x = 0;
+    while x < POINTS {
+        perm[x] = x as u8;
+        x += 1;
+    }
+    perm
+};
+
+fn to_perm(state: &NormalizedSkewb) -> Perm {
+    let solved = NormalizedSkewb::new();
+    let mut perm = IDENTITY;
+    for i in 0..6 {
+        let home = solved
+            .center_pieces
+            .iter()
+            .position(|&c| c == state.center_pieces[i])
+            .unwrap();
+        let turn = state.center_orientations.map_or(0, |o| o[i] as usize);
+        for a in 0..4 {
+            perm[4 * home + a] = (4 * i + (a + turn) % 4) as u8;
+        }
+    }
+    for i in 0..4 {
+        let piece = state.floating_pieces[i];
+        let floating_twist = orientation_to_i(state.floating_orientations[i]);
+        let fixed_twist = orientation_to_i(state.fixed_orientations[i]);
+        for a in 0..3 {
+            perm[24 + 3 * piece + a] = (24 + 3 * i + (a + floating_twist) % 3) as u8;
+            perm[36 + 3 * i + a] = (36 + 3 * i + (a + fixed_twist) % 3) as u8;
+        }
+    }
+    perm
+}
+
+fn compose(a: &Perm, b: &Perm) -> Perm {
+    let mut perm = IDENTITY;
+    for x in 0..POINTS {
+        perm[x] = b[a[x] as usize];
+    }
+    perm
+}
+
+fn inverse(a: &Perm) -> Perm {
+    let mut perm = IDENTITY;
+    for x in 0..POINTS {
+        perm[a[x] as usize] = x as u8;
+    }
+    perm
+}
+
+// One link of the stabilizer chain: the group fixing the base points of the links before it.
+struct Level {
+    base: usize,
+    // The strong generators that fix every earlier base point but not this one.
+    generators: Vec<Perm>,
+    // For each point in the orbit of the base, an element taking the base there.
+    transversal: Vec<Option<Perm>>,
+}
+
+// The subgroup of the puzzle generated by some move sequences, found with the Schreier-Sims
+// algorithm: a chain of stabilizers whose orbits give the order of the subgroup and which sifts
+// any state down to the identity if and only if the state is in it. Only states that turn no
+// centre are in a subgroup generated by states that aren't supercubes, so generate it from
+// supercube states to ask about supercube states.
+pub struct Subgroup {
+    levels: Vec<Level>,
+}
+
+impl Subgroup {
+    pub fn generated_by(sequences: &[Vec<Move>]) -> Subgroup {
+        let states: Vec<NormalizedSkewb> = sequences
+            .iter()
+            .map(|moves| NormalizedSkewb::after(moves))
+            .collect();
+        Subgroup::generated_by_states(&states)
+    }
+
+    // The subgroup generated by what reaching each state from solved does.
+    pub fn generated_by_states(states: &[NormalizedSkewb]) -> Subgroup {
+        let mut subgroup = Subgroup { levels: vec![] };
+        for state in states.iter() {
+            let (level, residue) = subgroup.sift(to_perm(state), 0);
+            if residue != IDENTITY {
+                subgroup.add(level, residue);
+            }
+        }
+
+        // Every Schreier generator of every level has to sift through the levels below it. Adding
+        // a strong generator changes the orbits, so start over whenever one is added.
+        'complete: loop {
+            for i in (0..subgroup.levels.len()).rev() {
+                for x in 0..POINTS {
+                    let u = match subgroup.levels[i].transversal[x] {
+                        Some(u) => u,
+                        None => continue,
+                    };
+                    for s in subgroup.generators_from(i) {
+                        let us = compose(&u, &s);
+                        let v = subgroup.levels[i].transversal
+                            [us[subgroup.levels[i].base] as usize]
+                            .unwrap();
+                        let (level, residue) = subgroup.sift(compose(&us, &inverse(&v)), i + 1);
+                        if residue != IDENTITY {
+                            subgroup.add(level, residue);
+                            continue 'complete;
+                        }
+                    }
+                }
+            }
+            return subgroup;
+        }
+    }
+
+    // The strong generators of the i'th group in the chain.
+    fn generators_from(&self, i: usize) -> Vec<Perm> {
+        self.levels[i..]
+            .iter()
+            .flat_map(|level| level.generators.iter().cloned())
+            .collect()
+    }
+
+    // Adds a strong generator that fixes the base points before the level, starting a new level
+    // if it fixes all of them.
+    fn add(&mut self, level: usize, generator: Perm) {
+        if level == self.levels.len() {
+            let base = (0..POINTS).find(|&x| generator[x] as usize != x).unwrap();
+            self.levels.push(Level {
+                base,
+                generators: vec![],
+                transversal: vec![],
+            });
+        }
+        self.levels[level].generators.push(generator);
+        for i in 0..=level {
+            self.update_transversal(i);
+        }
+    }
+
+    fn update_transversal(&mut self, i: usize) {
+        let generators = self.generators_from(i);
+        let base = self.levels[i].base;
+        let mut transversal = vec![None; POINTS];
+        transversal[base] = Some(IDENTITY);
+        let mut frontier = vec![base];
+        while let Some(x) = frontier.pop() {
+            let u = transversal[x].unwrap();
+            for s in generators.iter() {
+                let y = s[x] as usize;
+                if transversal[y].is_none() {
+                    transversal[y] = Some(compose(&u, s));
+                    frontier.push(y);
+                }
+            }
+        }
+        self.levels[i].transversal = transversal;
+    }
+
+    // Divides out the transversal elements from the level on, returning where the element fell
+    // out of the chain and what was left of it, which is the identity if it got all the way.
+    fn sift(&self, mut perm: Perm, from: usize) -> (usize, Perm) {
+        for i in from..self.levels.len() {
+            let level = &self.levels[i];
+            match level.transversal[perm[level.base] as usize] {
+                Some(u) => perm = compose(&perm, &inverse(&u)),
+                None => return (i, perm),
+            }
+        }
+        (self.levels.len(), perm)
+    }
+
+    pub fn order(&self) -> u64 {
+        self.levels
+            .iter()
+            .map(|level| level.transversal.iter().filter(|u| u.is_some()).count() as u64)
+            .product()
+    }
+
+    pub fn contains(&self, state: &NormalizedSkewb) -> bool {
+        self.sift(to_perm(state), 0).1 == IDENTITY
+    }
+}
+
+#[test]
+fn permutations_agree_with_states() {
+    use solver::scramble;
+
+    let (a, b) = (scramble(1, 20), scramble(2, 20));
+    assert_eq!(to_perm(&a.compose(&b)), compose(&to_perm(&a), &to_perm(&b)));
+    assert_eq!(to_perm(&a.inverse()), inverse(&to_perm(&a)));
+    assert_eq!(IDENTITY, to_perm(&NormalizedSkewb::new()));
+}
+
+#[test]
+fn supercube_permutations() {
+    use skewb::STATE_COUNT;
+    use solver::scramble_moves;
+
+    let supercube = NormalizedSkewb::supercube();
+    let a = supercube.applied(&scramble_moves(1, 20));
+    let b = supercube.applied(&scramble_moves(2, 20));
+    assert_eq!(to_perm(&a.compose(&b)), compose(&to_perm(&a), &to_perm(&b)));
+    assert_eq!(to_perm(&a.inverse()), inverse(&to_perm(&a)));
+    assert_eq!(IDENTITY, to_perm(&supercube));
+
+    // Turning centres in place is a different permutation from leaving them.
+    let mut twisted = NormalizedSkewb::supercube();
+    twisted.center_orientations = Some([2, 2, 2, 2, 0, 0]);
+    assert!(to_perm(&twisted) != IDENTITY);
+
+    let generators: Vec<NormalizedSkewb> = Move::all()
+        .into_iter()
+        .map(|m| supercube.applied(&[m]))
+        .collect();
+    let whole = Subgroup::generated_by_states(&generators);
+    assert!(whole.order() > STATE_COUNT as u64);
+    assert!(whole.contains(&a));
+    let u = Subgroup::generated_by_states(&generators[..1]);
+    assert!(!u.contains(&twisted));
+}
+
+#[test]
+fn subgroup_orders() {
+    use skewb::{Direction, STATE_COUNT};
+    use table::DepthReport;
+
+    let whole = Subgroup::generated_by(&[
+        vec![Move::new((0, 0, 0), Direction::LR)],
+        vec![Move::new((0, 1, 1), Direction::LR)],
+        vec![Move::new((1, 0, 1), Direction::LR)],
+        vec![Move::new((1, 1, 0), Direction::LR)],
+    ]);
+    assert_eq!(STATE_COUNT as u64, whole.order());
+
+    let u = Subgroup::generated_by(&[vec![Move::new((0, 0, 0), Direction::FB)]]);
+    assert_eq!(3, u.order());
+
+    let corners = [(0, 0, 0), (1, 0, 1)];
+    let two = Subgroup::generated_by(
+        &corners
+            .iter()
+            .map(|&c| vec![Move::new(c, Direction::LR)])
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(DepthReport::for_corners(&corners).group_size, two.order());
+
+    assert_eq!(1, Subgroup::generated_by(&[vec![]]).order());
+}
+
+#[test]
+fn membership() {
+    use skewb::Direction;
+    use solver::scramble;
+
+    let r = Move::new((1, 1, 0), Direction::LR);
+    let u = Move::new((0, 0, 0), Direction::LR);
+    let sexy = vec![r, u, r.inverse(), u.inverse()];
+    let twice = NormalizedSkewb::after(&[sexy.clone(), sexy.clone()].concat());
+    let subgroup = Subgroup::generated_by(&[sexy]);
+
+    assert!(subgroup.contains(&twice));
+    assert!(subgroup.contains(&NormalizedSkewb::new()));
+    assert!(!subgroup.contains(&NormalizedSkewb::after(&[r])));
+
+    let whole =
+        Subgroup::generated_by(&Move::all().into_iter().map(|m| vec![m]).collect::<Vec<_>>());
+    for seed in 0..5 {
+        assert!(whole.contains(&scramble(seed, 25)));
+    }
+}
@@ -1,6 +1,7 @@
-use std::collections::HashSet;
 use std::ops::{Add, AddAssign, Sub};
 
+use solver::{Iddfs, SearchConfig, SearchError, Solver, TableLookup};
+
 pub type Corner = (u8, u8, u8);
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -84,25 +85,126 @@ fn rotate_elements<V>(array: &mut [V], keys: &[usize]) {
     }
 }
 
-#[derive(Debug)]
+// Supercube centres: each centre carries a marker, and its orientation is how many quarter turns
+// clockwise the marker is from pointing at the next axis round from the centre's own, towards the
+// D, R or F side. Directions are axis vectors with coordinates for U to D, L to R and B to F, each
+// -1 or 1. A corner turn takes that next axis to the next axis of where the centre goes, so on a
+// skewb centres only ever turn by half turns. Whole rotations can leave them quarter turned.
+pub(crate) type Vector = [i8; 3];
+
+pub(crate) fn center_normal(c: Center) -> Vector {
+    match c {
+        Center::U => [-1, 0, 0],
+        Center::D => [1, 0, 0],
+        Center::L => [0, -1, 0],
+        Center::R => [0, 1, 0],
+        Center::B => [0, 0, -1],
+        Center::F => [0, 0, 1],
+    }
+}
+
+fn center_from_normal(n: Vector) -> Center {
+    *CENTERS.iter().find(|&&c| center_normal(c) == n).unwrap()
+}
+
+pub(crate) fn center_direction(c: Center, turns: u8) -> Vector {
+    let n = center_normal(c);
+    let a = n.iter().position(|&x| x != 0).unwrap();
+    let mut v = [0; 3];
+    v[(a + 1) % 3] = 1;
+    for _ in 0..turns {
+        // Looking at the face, a clockwise quarter turn takes v to v x n.
+        v = [
+            v[1] * n[2] - v[2] * n[1],
+            v[2] * n[0] - v[0] * n[2],
+            v[0] * n[1] - v[1] * n[0],
+        ];
+    }
+    v
+}
+
+// Moves the markers of the given centres the way map moves directions, to wherever map takes the
+// centres. The centres themselves are moved separately.
+fn turn_center_orientations(
+    orientations: &mut Option<[u8; 6]>,
+    centers: &[Center],
+    map: &dyn Fn(Vector) -> Vector,
+) {
+    if let Some(old) = *orientations {
+        let mut new = old;
+        for &c in centers.iter() {
+            let to = center_from_normal(map(center_normal(c)));
+            let v = map(center_direction(c, old[Skewb::center_to_i(c)]));
+            new[Skewb::center_to_i(to)] = (0..4).find(|&k| center_direction(to, k) == v).unwrap();
+        }
+        *orientations = Some(new);
+    }
+}
+
+// What turn_lr does to the three centres around c.
+fn turn_corner_centers(orientations: &mut Option<[u8; 6]>, c: Corner) {
+    let s = [2 * c.0 as i8 - 1, 2 * c.1 as i8 - 1, 2 * c.2 as i8 - 1];
+    let centers: Vec<Center> = (0..3)
+        .map(|a| {
+            let mut n = [0; 3];
+            n[a] = s[a];
+            center_from_normal(n)
+        })
+        .collect();
+    let turn = |x: Vector| [s[0] * s[2] * x[2], s[1] * s[0] * x[0], s[2] * s[1] * x[1]];
+    turn_center_orientations(orientations, &centers, &turn);
+}
+
+// Centre orientations counted from the orientation each centre has in start, where center_from[i]
+// is the position in start of the centre now at position i. Every turn and rotation adds the same
+// quarter turns to a centre whichever way round it already is, so they just subtract.
+fn relative_center_orientations(
+    orientations: Option<[u8; 6]>,
+    start: Option<[u8; 6]>,
+    center_from: &[usize; 6],
+) -> Option<[u8; 6]> {
+    let (orientations, start) = (orientations?, start?);
+    let mut relative = [0; 6];
+    for i in 0..6 {
+        relative[i] = (orientations[i] + 4 - start[center_from[i]]) % 4;
+    }
+    Some(relative)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Skewb {
     corner_pieces: [usize; 8],
     corner_orientations: [Orientation; 8],
     center_pieces: [Color; 6],
+    // Only tracked on a supercube.
+    center_orientations: Option<[u8; 6]>,
     even_rotation: bool,
 }
 
+impl Default for Skewb {
+    fn default() -> Skewb { Skewb::new() }
+}
+
 impl Skewb {
     pub fn new() -> Skewb {
         Skewb {
             corner_pieces: [0, 1, 2, 3, 4, 5, 6, 7],
             corner_orientations: [Orientation::UD; 8],
             center_pieces: [Color::Y, Color::B, Color::R, Color::G, Color::O, Color::W],
+            center_orientations: None,
             // TODO: this can be computed in a method; we don't need to store this.
             even_rotation: true,
         }
     }
 
+    // A solved skewb that keeps track of which way round its centres are.
+    pub fn supercube() -> Skewb {
+        Skewb {
+            center_orientations: Some([0; 6]),
+            ..Skewb::new()
+        }
+    }
+
     fn corner_to_i(c: Corner) -> usize {
         match c {
             (0, 0, 0) => 0,
@@ -113,7 +215,7 @@ impl Skewb {
             (1, 0, 1) => 5,
             (1, 1, 1) => 6,
             (1, 1, 0) => 7,
-            x => panic!(format!("{:?} not a corner", x)),
+            x => panic!("{:?} not a corner", x),
         }
     }
     fn i_to_corner_piece(&self, i: usize) -> CornerPiece {
@@ -127,7 +229,7 @@ impl Skewb {
                 5 => CornerPiece(Color::W, Color::O, Color::B),
                 6 => CornerPiece(Color::W, Color::R, Color::B),
                 7 => CornerPiece(Color::W, Color::R, Color::G),
-                x => panic!(format!("{:?} not a corner piece index", x)),
+                x => panic!("{:?} not a corner piece index", x),
             }
         } else {
             match i {
@@ -139,7 +241,7 @@ impl Skewb {
                 5 => CornerPiece(Color::W, Color::B, Color::O),
                 6 => CornerPiece(Color::W, Color::B, Color::R),
                 7 => CornerPiece(Color::W, Color::G, Color::R),
-                x => panic!(format!("{:?} not a corner piece index", x)),
+                x => panic!("{:?} not a corner piece index", x),
             }
         }
     }
@@ -161,6 +263,16 @@ impl Skewb {
         self.corner_orientations[Self::corner_to_i(c)]
     }
     pub fn get_center_piece(&self, c: Center) -> Color { self.center_pieces[Self::center_to_i(c)] }
+    // How many quarter turns the centre is from its marker's reference direction, on a supercube.
+    pub fn get_center_orientation(&self, c: Center) -> Option<u8> {
+        self.center_orientations
+            .map(|orientations| orientations[Self::center_to_i(c)])
+    }
+    // Where the marker on the centre points, as an axis vector, on a supercube.
+    pub fn get_center_direction(&self, c: Center) -> Option<[i8; 3]> {
+        self.get_center_orientation(c)
+            .map(|turns| center_direction(c, turns))
+    }
 
     pub fn turn_lr(&mut self, c: Corner) {
         let corners: Vec<usize> = [
@@ -193,6 +305,7 @@ impl Skewb {
         .map(|x| Self::center_to_i(*x))
         .collect();
         rotate_elements(&mut self.center_pieces, &centers);
+        turn_corner_centers(&mut self.center_orientations, c);
     }
     pub fn turn_fb(&mut self, c: Corner) {
         self.turn_lr(c);
@@ -221,6 +334,8 @@ impl Skewb {
             .map(|x| Self::center_to_i(*x))
             .collect();
         rotate_elements(&mut self.center_pieces, &centers);
+        let turn = |x: Vector| [x[0], -x[2], x[1]];
+        turn_center_orientations(&mut self.center_orientations, &CENTERS, &turn);
 
         self.even_rotation = !self.even_rotation;
         for i in 0..8 {
@@ -254,6 +369,8 @@ impl Skewb {
             .map(|x| Self::center_to_i(*x))
             .collect();
         rotate_elements(&mut self.center_pieces, &centers);
+        let turn = |x: Vector| [x[1], -x[0], x[2]];
+        turn_center_orientations(&mut self.center_orientations, &CENTERS, &turn);
 
         self.even_rotation = !self.even_rotation;
         for i in 0..8 {
@@ -287,6 +404,8 @@ impl Skewb {
             .map(|x| Self::center_to_i(*x))
             .collect();
         rotate_elements(&mut self.center_pieces, &centers);
+        let turn = |x: Vector| [-x[2], x[1], x[0]];
+        turn_center_orientations(&mut self.center_orientations, &CENTERS, &turn);
 
         self.even_rotation = !self.even_rotation;
         for i in 0..8 {
@@ -298,6 +417,120 @@ impl Skewb {
         }
     }
 
+    fn i_to_corner(i: usize) -> Corner {
+        [
+            (0, 0, 0),
+            (0, 0, 1),
+            (0, 1, 1),
+            (0, 1, 0),
+            (1, 0, 0),
+            (1, 0, 1),
+            (1, 1, 1),
+            (1, 1, 0),
+        ][i]
+    }
+
+    // The mirror image across the plane between the two faces on the given axis, with the colours
+    // of those faces swapped so that it is a state of the same puzzle: the state the mirrored moves
+    // leave a solved puzzle in. Every piece keeps its orientation, since a reflection across this
+    // plane swaps fixed and floating positions, and the same orientation is twisted the other way
+    // round on those.
+    pub fn mirror(&self, axis: Orientation) -> Skewb {
+        let mirror_corner = |c: Corner| mirror_corner(c, axis);
+        let mut mirrored = Skewb::new();
+        mirrored.even_rotation = self.even_rotation;
+        for i in 0..8 {
+            let j = Self::corner_to_i(mirror_corner(Self::i_to_corner(i)));
+            let piece = Self::i_to_corner(self.corner_pieces[i]);
+            mirrored.corner_pieces[j] = Self::corner_to_i(mirror_corner(piece));
+            mirrored.corner_orientations[j] = self.corner_orientations[i];
+        }
+        let faces = match axis {
+            Orientation::UD => [Center::U, Center::D],
+            Orientation::LR => [Center::L, Center::R],
+            Orientation::FB => [Center::F, Center::B],
+        };
+        let solved = Skewb::new().center_pieces;
+        let colors = [
+            solved[Self::center_to_i(faces[0])],
+            solved[Self::center_to_i(faces[1])],
+        ];
+        let swap = |c: Color| match colors.iter().position(|&d| d == c) {
+            Some(k) => colors[1 - k],
+            None => c,
+        };
+        for &center in CENTERS.iter() {
+            let mirrored_center = if center == faces[0] {
+                faces[1]
+            } else if center == faces[1] {
+                faces[0]
+            } else {
+                center
+            };
+            mirrored.center_pieces[Self::center_to_i(mirrored_center)] =
+                swap(self.center_pieces[Self::center_to_i(center)]);
+        }
+
+        // The markers are reflected too, and like the colours they are then counted from where
+        // the reflection takes the markers of a solved puzzle.
+        let a = match axis {
+            Orientation::UD => 0,
+            Orientation::LR => 1,
+            Orientation::FB => 2,
+        };
+        let reflect = |mut x: Vector| {
+            x[a] = -x[a];
+            x
+        };
+        let mut reflected = self.center_orientations;
+        turn_center_orientations(&mut reflected, &CENTERS, &reflect);
+        let mut reflected_solved = Some([0; 6]);
+        turn_center_orientations(&mut reflected_solved, &CENTERS, &reflect);
+        let mut center_from = [0; 6];
+        for (i, from) in center_from.iter_mut().enumerate() {
+            *from = solved
+                .iter()
+                .position(|&c| c == mirrored.center_pieces[i])
+                .unwrap();
+        }
+        mirrored.center_orientations =
+            relative_center_orientations(reflected, reflected_solved, &center_from);
+        mirrored
+    }
+
+    // The state that whatever moves take start to self leave a solved puzzle in. The pieces and
+    // colours are renamed after where start has them, and orientations are counted from theirs,
+    // since every move adds the same twist whatever piece it turns.
+    pub(crate) fn relative_to(&self, start: &Skewb) -> Skewb {
+        let solved = Skewb::new();
+        let mut relative = Skewb::new();
+        let mut center_from = [0; 6];
+        for i in 0..8 {
+            let from = start
+                .corner_pieces
+                .iter()
+                .position(|&p| p == self.corner_pieces[i])
+                .unwrap();
+            relative.corner_pieces[i] = from;
+            relative.corner_orientations[i] =
+                self.corner_orientations[i] - start.corner_orientations[from];
+        }
+        for (i, from) in center_from.iter_mut().enumerate() {
+            *from = start
+                .center_pieces
+                .iter()
+                .position(|&c| c == self.center_pieces[i])
+                .unwrap();
+            relative.center_pieces[i] = solved.center_pieces[*from];
+        }
+        relative.center_orientations = relative_center_orientations(
+            self.center_orientations,
+            start.center_orientations,
+            &center_from,
+        );
+        relative
+    }
+
     fn i_to_floating_i(i: usize) -> usize {
         match i {
             1 => 0,
@@ -308,6 +541,24 @@ impl Skewb {
         }
     }
 
+    // Rotates the skewb into the canonical position, trying every way of holding it. Returns
+    // false if no rotation puts the fixed corners in place.
+    pub(crate) fn rotate_to_canonical(&mut self) -> bool {
+        for _ in 0..4 {
+            for _ in 0..4 {
+                for _ in 0..4 {
+                    if self.corner_pieces[0] == 0 && self.corner_pieces[2] == 2 {
+                        return true;
+                    }
+                    self.rotate_ud();
+                }
+                self.rotate_fb();
+            }
+            self.rotate_lr();
+        }
+        false
+    }
+
     pub fn normalize(self) -> NormalizedSkewb {
         // TODO: Automatically rotate self so the fixed corners are permuted correctly
         if self.corner_pieces[0] != 0 && self.corner_pieces[2] != 2 {
@@ -333,6 +584,7 @@ impl Skewb {
         ];
         NormalizedSkewb {
             center_pieces: self.center_pieces,
+            center_orientations: self.center_orientations,
             fixed_orientations,
             floating_orientations,
             floating_pieces,
@@ -346,6 +598,8 @@ pub struct NormalizedSkewb {
     pub floating_pieces: [usize; 4],
     pub floating_orientations: [Orientation; 4],
     pub center_pieces: [Color; 6],
+    // As for Skewb, only tracked on a supercube. Ranks and the tables built from them ignore it.
+    pub center_orientations: Option<[u8; 6]>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -354,6 +608,10 @@ enum FixedOrFloating {
     Floating,
 }
 
+impl Default for NormalizedSkewb {
+    fn default() -> NormalizedSkewb { NormalizedSkewb::new() }
+}
+
 impl NormalizedSkewb {
     pub fn new() -> NormalizedSkewb {
         NormalizedSkewb {
@@ -361,6 +619,22 @@ impl NormalizedSkewb {
             floating_pieces: [0, 1, 2, 3],
             floating_orientations: [Orientation::UD; 4],
             center_pieces: [Color::Y, Color::B, Color::R, Color::G, Color::O, Color::W],
+            center_orientations: None,
+        }
+    }
+
+    pub fn supercube() -> NormalizedSkewb {
+        NormalizedSkewb {
+            center_orientations: Some([0; 6]),
+            ..NormalizedSkewb::new()
+        }
+    }
+
+    // The solved state of the same kind of puzzle, a supercube or not.
+    pub fn solved(&self) -> NormalizedSkewb {
+        NormalizedSkewb {
+            center_orientations: self.center_orientations.map(|_| [0; 6]),
+            ..NormalizedSkewb::new()
         }
     }
 
@@ -374,10 +648,10 @@ impl NormalizedSkewb {
             (1, 0, 1) => (FixedOrFloating::Fixed, 2),
             (1, 1, 1) => (FixedOrFloating::Floating, 3),
             (1, 1, 0) => (FixedOrFloating::Fixed, 3),
-            x => panic!(format!("{:?} not a corner", x)),
+            x => panic!("{:?} not a corner", x),
         }
     }
-    fn center_to_i(c: Center) -> usize {
+    pub(crate) fn center_to_i(c: Center) -> usize {
         match c {
             Center::U => 0,
             Center::F => 1,
@@ -422,6 +696,7 @@ impl NormalizedSkewb {
         .map(|x| Self::center_to_i(*x))
         .collect();
         rotate_elements(&mut self.center_pieces, &centers);
+        turn_corner_centers(&mut self.center_orientations, c);
     }
     pub fn turn_fb(&mut self, c: Corner) {
         self.turn_lr(c);
@@ -461,11 +736,19 @@ impl NormalizedSkewb {
         ];
         Skewb {
             center_pieces: self.center_pieces,
+            center_orientations: self.center_orientations,
             corner_orientations,
             corner_pieces,
             even_rotation: true,
         }
     }
+
+    // The mirror image as for Skewb::mirror, rotated back into the canonical position.
+    pub fn mirror(&self, axis: Orientation) -> NormalizedSkewb {
+        let mut mirrored = self.clone().denormalize().mirror(axis);
+        mirrored.rotate_to_canonical();
+        mirrored.normalize()
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -474,12 +757,136 @@ pub enum Direction {
     FB,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+impl Direction {
+    pub fn inverse(self) -> Direction {
+        match self {
+            Direction::LR => Direction::FB,
+            Direction::FB => Direction::LR,
+        }
+    }
+}
+
+// The corners a NormalizedSkewb can be turned around, in the order the solvers try them.
+pub const FIXED_CORNERS: [Corner; 4] = [(0, 0, 0), (0, 1, 1), (1, 0, 1), (1, 1, 0)];
+
+// The other corners, in the order of NormalizedSkewb::floating_pieces.
+pub const FLOATING_CORNERS: [Corner; 4] = [(0, 0, 1), (0, 1, 0), (1, 0, 0), (1, 1, 1)];
+
+// The centres in the order of NormalizedSkewb::center_pieces.
+pub const CENTERS: [Center; 6] = [
+    Center::U,
+    Center::F,
+    Center::R,
+    Center::B,
+    Center::L,
+    Center::D,
+];
+
+// Where a corner is across the plane between the two faces on the given axis.
+pub fn mirror_corner(c: Corner, axis: Orientation) -> Corner {
+    match axis {
+        Orientation::UD => (1 - c.0, c.1, c.2),
+        Orientation::LR => (c.0, 1 - c.1, c.2),
+        Orientation::FB => (c.0, c.1, 1 - c.2),
+    }
+}
+
+// The faces a corner is on, like UFR.
+pub fn corner_name(c: Corner) -> String {
+    format!(
+        "{}{}{}",
+        ["U", "D"][c.0 as usize],
+        ["B", "F"][c.2 as usize],
+        ["L", "R"][c.1 as usize]
+    )
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Move {
     direction: Direction,
     corner: Corner,
 }
 
+impl Move {
+    pub fn new(corner: Corner, direction: Direction) -> Move { Move { direction, corner } }
+    pub fn corner(&self) -> Corner { self.corner }
+    pub fn direction(&self) -> Direction { self.direction }
+    pub fn inverse(&self) -> Move { Move::new(self.corner, self.direction.inverse()) }
+
+    // The mirror image of the move, as for Skewb::mirror. A reflection turns the other way round
+    // but also swaps fixed and floating corners, so the direction stays the same.
+    pub fn mirror(&self, axis: Orientation) -> Move {
+        Move::new(mirror_corner(self.corner, axis), self.direction)
+    }
+
+    // Every move of a NormalizedSkewb, in the order the solvers try them.
+    pub fn all() -> Vec<Move> {
+        let mut moves = vec![];
+        for &corner in FIXED_CORNERS.iter() {
+            for &direction in [Direction::FB, Direction::LR].iter() {
+                moves.push(Move::new(corner, direction));
+            }
+        }
+        moves
+    }
+}
+
+// The moves a search may use: turns of some of the fixed corners, optionally only in one
+// direction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MoveSet {
+    pub corners: Vec<Corner>,
+    pub direction: Option<Direction>,
+}
+
+impl Default for MoveSet {
+    fn default() -> MoveSet { MoveSet::all() }
+}
+
+impl MoveSet {
+    pub fn all() -> MoveSet { MoveSet::new(&FIXED_CORNERS, None) }
+
+    // The corners are kept sorted and without repeats, so that the same moves make equal sets
+    // however the corners were listed.
+    pub fn new(corners: &[Corner], direction: Option<Direction>) -> MoveSet {
+        for c in corners.iter() {
+            assert!(FIXED_CORNERS.contains(c), "{:?} is not a fixed corner", c);
+        }
+        let mut corners = corners.to_vec();
+        corners.sort();
+        corners.dedup();
+        MoveSet { corners, direction }
+    }
+
+    pub fn contains(&self, move_: &Move) -> bool {
+        self.corners.contains(&move_.corner) && self.direction.is_none_or(|d| d == move_.direction)
+    }
+
+    // The allowed moves, in the order of Move::all.
+    pub fn moves(&self) -> Vec<Move> {
+        Move::all()
+            .into_iter()
+            .filter(|m| self.contains(m))
+            .collect()
+    }
+}
+
+impl Skewb {
+    // Unlike a NormalizedSkewb, a Skewb can be turned around any corner, fixed or floating.
+    pub fn do_move(&mut self, move_: &Move) {
+        match move_.direction {
+            Direction::FB => self.turn_fb(move_.corner),
+            Direction::LR => self.turn_lr(move_.corner),
+        }
+    }
+    pub fn undo_move(&mut self, move_: &Move) {
+        match move_.direction {
+            Direction::LR => self.turn_fb(move_.corner),
+            Direction::FB => self.turn_lr(move_.corner),
+        }
+    }
+}
+
 impl NormalizedSkewb {
     pub fn do_move(&mut self, move_: &Move) {
         match move_.direction {
@@ -494,56 +901,204 @@ impl NormalizedSkewb {
         }
     }
 
-    pub fn is_solved(&self) -> bool { *self == NormalizedSkewb::new() }
+    // The state reached by doing the moves to a solved puzzle.
+    pub fn after(moves: &[Move]) -> NormalizedSkewb { NormalizedSkewb::new().applied(moves) }
 
-    fn _solution(
-        &mut self,
-        move_stack: &mut Vec<Move>,
-        discovered: &mut HashSet<NormalizedSkewb>,
-        max_length: usize,
-    ) -> bool
-    {
-        if self.is_solved() {
-            return true;
-        } else if move_stack.len() >= max_length || discovered.contains(self) {
-            return false;
+    // The state reached by doing the moves to self.
+    pub fn applied(&self, moves: &[Move]) -> NormalizedSkewb {
+        let mut state = self.clone();
+        for move_ in moves.iter() {
+            state.do_move(move_);
         }
-        discovered.insert(self.clone());
+        state
+    }
 
-        for corner in vec![(0, 0, 0), (0, 1, 1), (1, 0, 1), (1, 1, 0)].into_iter() {
-            if let Some(last_move) = move_stack.last() {
-                if last_move.corner == corner {
-                    continue;
-                }
-            }
+    // The state each move leads to, in the order of Move::all.
+    pub fn neighbors(&self) -> Vec<(Move, NormalizedSkewb)> {
+        Move::all()
+            .into_iter()
+            .map(|move_| {
+                let mut next = self.clone();
+                next.do_move(&move_);
+                (move_, next)
+            })
+            .collect()
+    }
 
-            for direction in vec![Direction::FB, Direction::LR].into_iter() {
-                let move_ = Move { direction, corner };
-                self.do_move(&move_);
-                move_stack.push(move_.clone());
-                let has_solution = self._solution(move_stack, discovered, max_length);
-                self.undo_move(&move_);
-                if has_solution {
-                    return true;
-                } else {
-                    move_stack.pop();
-                }
-            }
+    pub fn is_solved(&self) -> bool { *self == self.solved() }
+
+    pub fn solution(&mut self) -> Option<Vec<Move>> {
+        Iddfs.solve(self, &SearchConfig::default()).ok()
+    }
+
+    // The shortest solution using only the given moves, or OutsideSubgroup if they can't solve
+    // it at all.
+    pub fn restricted_solution(&self, moves: &MoveSet) -> Result<Vec<Move>, SearchError> {
+        let config = SearchConfig {
+            moves: moves.clone(),
+            ..SearchConfig::default()
+        };
+        TableLookup::shared(moves).solve(self, &config)
+    }
+
+    // The state that has the same solutions as the path from self to target, i.e. self with
+    // every piece relabelled by where target keeps it.
+    pub(crate) fn relative_to(&self, target: &NormalizedSkewb) -> NormalizedSkewb {
+        let solved = NormalizedSkewb::new();
+        let mut relative = NormalizedSkewb::new();
+        let mut center_from = [0; 6];
+        for (i, j) in center_from.iter_mut().enumerate() {
+            *j = target
+                .center_pieces
+                .iter()
+                .position(|&c| c == self.center_pieces[i])
+                .unwrap();
+            relative.center_pieces[i] = solved.center_pieces[*j];
         }
-        discovered.remove(self);
-        return false;
+        relative.center_orientations = relative_center_orientations(
+            self.center_orientations,
+            target.center_orientations,
+            &center_from,
+        );
+        for i in 0..4 {
+            let j = target
+                .floating_pieces
+                .iter()
+                .position(|&p| p == self.floating_pieces[i])
+                .unwrap();
+            relative.floating_pieces[i] = j;
+            relative.floating_orientations[i] =
+                self.floating_orientations[i] - target.floating_orientations[j];
+            relative.fixed_orientations[i] =
+                self.fixed_orientations[i] - target.fixed_orientations[i];
+        }
+        relative
     }
-    pub fn solution(&mut self) -> Option<Vec<Move>> {
-        // Iterative DFS
+}
 
-        for solution_length in 0..20 {
-            let mut discovered = HashSet::new();
-            let mut move_stack = vec![];
-            if self._solution(&mut move_stack, &mut discovered, solution_length) {
-                return Some(move_stack);
-            }
+// Ranking assigns every reachable NormalizedSkewb a distinct index below STATE_COUNT so that
+// tables over the whole puzzle can be plain arrays.
+//
+// Centers and floating pieces are always evenly permuted, the floating orientations always sum
+// to zero, and the fixed orientations sum to the class of the floating permutation modulo the
+// Klein four-group. So the last floating and fixed orientations don't need to be stored.
+//
+// The corners make up the low digits of a rank and the centers the high ones, and since turns
+// act on each independently, rank % CORNER_STATES and rank / CORNER_STATES can be turned
+// separately.
+
+pub(crate) const CORNER_STATES: usize = 12 * 27 * 27;
+pub const STATE_COUNT: usize = 360 * CORNER_STATES;
+
+pub(crate) fn orientation_to_i(o: Orientation) -> usize {
+    match o {
+        Orientation::UD => 0,
+        Orientation::LR => 1,
+        Orientation::FB => 2,
+    }
+}
+
+fn i_to_orientation(i: usize) -> Orientation {
+    match i % 3 {
+        0 => Orientation::UD,
+        1 => Orientation::LR,
+        _ => Orientation::FB,
+    }
+}
+
+fn color_to_i(c: Color) -> usize {
+    match c {
+        Color::Y => 0,
+        Color::B => 1,
+        Color::R => 2,
+        Color::G => 3,
+        Color::O => 4,
+        Color::W => 5,
+    }
+}
+
+fn i_to_color(i: usize) -> Color { [Color::Y, Color::B, Color::R, Color::G, Color::O, Color::W][i] }
+
+// Lehmer code of an even permutation, halved. Swapping the last two elements flips both the
+// parity and the lowest bit of the Lehmer code, so this is a bijection onto 0..n!/2.
+fn even_permutation_rank(p: &[usize]) -> usize {
+    let mut rank = 0;
+    for i in 0..p.len() {
+        let smaller = p[i + 1..].iter().filter(|&&x| x < p[i]).count();
+        rank = rank * (p.len() - i) + smaller;
+    }
+    rank / 2
+}
+
+fn even_permutation_unrank(rank: usize, n: usize) -> Vec<usize> {
+    let mut digits = vec![0; n];
+    let mut r = rank * 2;
+    for i in (0..n).rev() {
+        digits[i] = r % (n - i);
+        r /= n - i;
+    }
+    if digits.iter().sum::<usize>() % 2 == 1 {
+        digits[n - 2] ^= 1;
+    }
+    let mut unused: Vec<usize> = (0..n).collect();
+    digits.iter().map(|&d| unused.remove(d)).collect()
+}
+
+// Which coset of the Klein four-group an even permutation of the floating pieces lies in.
+fn floating_class(p: &[usize; 4]) -> usize {
+    let klein = [[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]];
+    let cosets = [[0, 1, 2, 3], [1, 2, 0, 3], [2, 0, 1, 3]];
+    for (class, q) in cosets.iter().enumerate() {
+        if klein.iter().any(|v| (0..4).all(|i| v[q[i]] == p[i])) {
+            return class;
         }
-        None
+    }
+    panic!("{:?} is not an even permutation", p)
+}
+
+impl NormalizedSkewb {
+    pub fn rank(&self) -> usize {
+        let centers: Vec<usize> = self.center_pieces.iter().map(|&c| color_to_i(c)).collect();
+        let mut rank = even_permutation_rank(&centers);
+        rank = rank * 12 + even_permutation_rank(&self.floating_pieces);
+        for &o in self.floating_orientations[..3].iter() {
+            rank = rank * 3 + orientation_to_i(o);
+        }
+        for &o in self.fixed_orientations[..3].iter() {
+            rank = rank * 3 + orientation_to_i(o);
+        }
+        rank
+    }
+
+    pub fn from_rank(rank: usize) -> NormalizedSkewb {
+        assert!(rank < STATE_COUNT, "{} is not a state rank", rank);
+        let mut skewb = NormalizedSkewb::new();
+        let mut r = rank;
+
+        let mut fixed_sum = 0;
+        for i in (0..3).rev() {
+            skewb.fixed_orientations[i] = i_to_orientation(r % 3);
+            fixed_sum += r % 3;
+            r /= 3;
+        }
+        let mut floating_sum = 0;
+        for i in (0..3).rev() {
+            skewb.floating_orientations[i] = i_to_orientation(r % 3);
+            floating_sum += r % 3;
+            r /= 3;
+        }
+        skewb.floating_orientations[3] = i_to_orientation(3 - floating_sum % 3);
+
+        let floating = even_permutation_unrank(r % 12, 4);
+        skewb.floating_pieces.copy_from_slice(&floating);
+        r /= 12;
+        let class = floating_class(&skewb.floating_pieces);
+        skewb.fixed_orientations[3] = i_to_orientation(3 + class - fixed_sum % 3);
+
+        for (i, c) in even_permutation_unrank(r, 6).into_iter().enumerate() {
+            skewb.center_pieces[i] = i_to_color(c);
+        }
+        skewb
     }
 }
 
@@ -628,3 +1183,150 @@ fn four_move_solution() {
     ];
     assert_eq!(expected, solution);
 }
+
+#[test]
+fn restricted_solution() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let mut sut = NormalizedSkewb::new();
+    sut.turn_fb((0, 0, 0));
+    let moves = MoveSet::new(&[(0, 0, 0)], Some(Direction::FB));
+    let expected = vec![
+        Move {
+            direction: Direction::FB,
+            corner: (0, 0, 0),
+        };
+        2
+    ];
+    assert_eq!(Ok(expected), sut.restricted_solution(&moves));
+
+    sut.turn_lr((0, 1, 1));
+    assert_eq!(
+        Err(SearchError::OutsideSubgroup),
+        sut.restricted_solution(&moves)
+    );
+    assert!(Arc::ptr_eq(
+        &TableLookup::shared(&moves),
+        &TableLookup::shared(&moves)
+    ));
+    let repeated = MoveSet::new(&[(0, 0, 0), (0, 0, 0)], Some(Direction::FB));
+    assert!(Arc::ptr_eq(
+        &TableLookup::shared(&moves),
+        &TableLookup::shared(&repeated)
+    ));
+
+    // Threads asking for the same moves at once still share one table.
+    let other = MoveSet::new(&[(0, 1, 1)], Some(Direction::LR));
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let other = other.clone();
+            thread::spawn(move || TableLookup::shared(&other))
+        })
+        .collect();
+    for handle in threads {
+        assert!(Arc::ptr_eq(
+            &TableLookup::shared(&other),
+            &handle.join().unwrap()
+        ));
+    }
+}
+
+#[test]
+fn mirroring_commutes_with_moving() {
+    use notation::fixed_corner_form;
+    use solver::scramble_moves;
+
+    for &axis in [Orientation::UD, Orientation::LR, Orientation::FB].iter() {
+        for seed in 0..10 {
+            let moves = scramble_moves(seed, 10);
+            let mirrored: Vec<Move> = moves.iter().map(|m| m.mirror(axis)).collect();
+
+            let mut skewb = Skewb::supercube();
+            let mut mirrored_skewb = Skewb::supercube();
+            for (move_, mirrored_move) in moves.iter().zip(mirrored.iter()) {
+                skewb.do_move(move_);
+                mirrored_skewb.do_move(mirrored_move);
+            }
+            assert_eq!(mirrored_skewb, skewb.mirror(axis));
+            assert_eq!(skewb, skewb.mirror(axis).mirror(axis));
+
+            let state = skewb.normalize();
+            let mut mirrored_state = NormalizedSkewb::supercube();
+            for move_ in fixed_corner_form(&mirrored).0.iter() {
+                mirrored_state.do_move(move_);
+            }
+            assert_eq!(mirrored_state, state.mirror(axis));
+            assert_eq!(state, state.mirror(axis).mirror(axis));
+        }
+    }
+}
+
+#[test]
+fn supercube_centers() {
+    use solver::scramble_moves;
+
+    let mut skewb = Skewb::supercube();
+    for move_ in scramble_moves(7, 20).iter() {
+        skewb.do_move(move_);
+    }
+    let before = skewb.clone();
+    for _ in 0..3 {
+        skewb.turn_lr((0, 1, 1));
+    }
+    assert_eq!(before, skewb);
+    for &c in CENTERS.iter() {
+        assert_eq!(0, skewb.get_center_orientation(c).unwrap() % 2);
+    }
+
+    // A quarter turn of the whole puzzle leaves the centres on its axis quarter turned.
+    skewb.rotate_ud();
+    assert_eq!(
+        1,
+        (4 + skewb.get_center_orientation(Center::U).unwrap()
+            - before.get_center_orientation(Center::U).unwrap())
+            % 2
+    );
+    for _ in 0..3 {
+        skewb.rotate_ud();
+    }
+    assert_eq!(before, skewb);
+    assert_eq!(before, before.clone().normalize().denormalize());
+
+    let mut one_move = Skewb::supercube();
+    one_move.turn_lr((0, 0, 0));
+    assert_eq!(Some(0), one_move.get_center_orientation(Center::U));
+    one_move.turn_lr((0, 1, 1));
+    assert!(CENTERS
+        .iter()
+        .any(|&c| one_move.get_center_orientation(c) == Some(2)));
+    assert_eq!(None, Skewb::new().get_center_orientation(Center::U));
+}
+
+#[test]
+fn neighbors() {
+    let state = NormalizedSkewb::new();
+    let neighbors = state.neighbors();
+    assert_eq!(Move::all().len(), neighbors.len());
+    for (move_, mut next) in neighbors {
+        next.undo_move(&move_);
+        assert_eq!(state, next);
+    }
+}
+
+#[test]
+fn after_moves() {
+    let moves = [
+        Move::new((0, 0, 0), Direction::LR),
+        Move::new((1, 0, 1), Direction::FB),
+    ];
+    let mut expected = NormalizedSkewb::new();
+    expected.turn_lr((0, 0, 0));
+    expected.turn_fb((1, 0, 1));
+    assert_eq!(expected, NormalizedSkewb::after(&moves));
+    assert_eq!(
+        NormalizedSkewb::after(&[moves[0], moves[1], moves[1]]),
+        NormalizedSkewb::after(&moves).applied(&moves[1..])
+    );
+    assert_eq!(NormalizedSkewb::new(), NormalizedSkewb::after(&[]));
+}
@@ -0,0 +1,945 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use batch::default_threads;
+use puzzle::TwistyPuzzle;
+use schreier_sims::Subgroup;
+use skewb::{Color, Move, MoveSet, NormalizedSkewb, Orientation};
+use table::DistanceTable;
+
+// Lets another thread stop a search. Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken { CancellationToken::default() }
+    pub fn cancel(&self) { self.0.store(true, Ordering::Relaxed) }
+    pub fn is_cancelled(&self) -> bool { self.0.load(Ordering::Relaxed) }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    // The solution length currently being searched.
+    pub depth: usize,
+    pub nodes_expanded: u64,
+}
+
+pub struct SearchConfig {
+    // The longest solution to look for.
+    pub max_depth: usize,
+    // Give up after expanding this many states.
+    pub node_limit: Option<u64>,
+    // Give up at this time.
+    pub deadline: Option<Instant>,
+    // Called whenever the search goes a level deeper, and every so often in between.
+    pub progress: Option<Box<dyn Fn(Progress) + Send + Sync>>,
+    pub cancellation: Option<CancellationToken>,
+    // The moves solutions may use.
+    pub moves: MoveSet,
+}
+
+impl Default for SearchConfig {
+    fn default() -> SearchConfig {
+        SearchConfig {
+            max_depth: 20,
+            node_limit: None,
+            deadline: None,
+            progress: None,
+            cancellation: None,
+            moves: MoveSet::all(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    // There is no solution of at most max_depth moves.
+    NotFound,
+    // The allowed moves can't get from start to target at any depth.
+    OutsideSubgroup,
+    // The solver can't search with the allowed moves.
+    UnsupportedMoves,
+    // The solver can't tell supercube states apart by their centre orientations.
+    UnsupportedSupercube,
+    NodeLimitReached,
+    DeadlinePassed,
+    Cancelled,
+}
+
+// How often to look at the clock, the cancellation token and the progress callback.
+const CHECK_INTERVAL: u64 = 4096;
+
+// Keeps track of how much work a search has done against its SearchConfig.
+struct Budget<'a> {
+    config: &'a SearchConfig,
+    depth: usize,
+    nodes_expanded: u64,
+}
+
+impl<'a> Budget<'a> {
+    fn new(config: &SearchConfig) -> Budget<'_> {
+        Budget {
+            config,
+            depth: 0,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn report(&self) {
+        if let Some(ref progress) = self.config.progress {
+            progress(Progress {
+                depth: self.depth,
+                nodes_expanded: self.nodes_expanded,
+            });
+        }
+    }
+
+    fn check(&self) -> Result<(), SearchError> {
+        let cancellation = self.config.cancellation.as_ref();
+        if cancellation.is_some_and(CancellationToken::is_cancelled) {
+            Err(SearchError::Cancelled)
+        } else if self.config.deadline.is_some_and(|d| Instant::now() >= d) {
+            Err(SearchError::DeadlinePassed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn deepen(&mut self, depth: usize) -> Result<(), SearchError> {
+        self.depth = depth;
+        self.report();
+        self.check()
+    }
+
+    fn expand(&mut self) -> Result<(), SearchError> {
+        if self.nodes_expanded >= self.config.node_limit.unwrap_or(u64::MAX) {
+            return Err(SearchError::NodeLimitReached);
+        }
+        self.nodes_expanded += 1;
+        if self.nodes_expanded.is_multiple_of(CHECK_INTERVAL) {
+            self.report();
+            self.check()?;
+        }
+        Ok(())
+    }
+}
+
+pub trait Solver {
+    // A shortest sequence of moves that takes start to target.
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError>;
+
+    fn solve(
+        &self,
+        start: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        self.solve_to(start, &start.solved(), config)
+    }
+}
+
+// Whether the allowed moves can take start to target at all, so that searches can report
+// OutsideSubgroup up front rather than searching every sequence up to max_depth first. A
+// supercube and a puzzle that isn't one can't be compared, so that's UnsupportedSupercube.
+fn reachable(
+    start: &NormalizedSkewb,
+    target: &NormalizedSkewb,
+    allowed: &MoveSet,
+) -> Result<(), SearchError> {
+    if start.center_orientations.is_some() != target.center_orientations.is_some() {
+        return Err(SearchError::UnsupportedSupercube);
+    }
+    let relative = start.relative_to(target);
+    let generators: Vec<NormalizedSkewb> = allowed
+        .moves()
+        .into_iter()
+        .map(|m| relative.solved().applied(&[m]))
+        .collect();
+    if Subgroup::generated_by_states(&generators).contains(&relative) {
+        Ok(())
+    } else {
+        Err(SearchError::OutsideSubgroup)
+    }
+}
+
+// Iterative deepening depth-first search. Needs no precomputation, but takes a while once
+// solutions get longer than about eight moves. It works for any TwistyPuzzle, not just skewbs.
+pub struct Iddfs;
+
+impl Iddfs {
+    fn search<P: TwistyPuzzle, F: Fn(&P) -> bool>(
+        state: &mut P,
+        is_target: &F,
+        move_stack: &mut Vec<P::Move>,
+        discovered: &mut HashSet<P>,
+        max_length: usize,
+        moves: &[P::Move],
+        budget: &mut Budget,
+    ) -> Result<bool, SearchError> {
+        if is_target(state) {
+            return Ok(true);
+        } else if move_stack.len() >= max_length || discovered.contains(state) {
+            return Ok(false);
+        }
+        budget.expand()?;
+        discovered.insert(state.clone());
+
+        for move_ in moves.iter() {
+            if P::redundant(move_stack, move_, moves) {
+                continue;
+            }
+            state.do_move(move_);
+            move_stack.push(*move_);
+            let has_solution = Self::search(
+                state, is_target, move_stack, discovered, max_length, moves, budget,
+            );
+            state.undo_move(move_);
+            if has_solution? {
+                return Ok(true);
+            } else {
+                move_stack.pop();
+            }
+        }
+        discovered.remove(state);
+        Ok(false)
+    }
+
+    // A shortest sequence of the given moves that takes start to target. The moves in the config
+    // are only for skewbs, so they're ignored.
+    pub fn solve_puzzle<P: TwistyPuzzle>(
+        &self,
+        start: &P,
+        target: &P,
+        moves: &[P::Move],
+        config: &SearchConfig,
+    ) -> Result<Vec<P::Move>, SearchError> {
+        Self::deepen(start, &|state: &P| state == target, moves, config)
+    }
+
+    // A shortest sequence of any of the puzzle's moves that solves it.
+    pub fn solve_any<P: TwistyPuzzle>(
+        &self,
+        start: &P,
+        config: &SearchConfig,
+    ) -> Result<Vec<P::Move>, SearchError> {
+        Self::deepen(start, &P::is_solved, &P::moves(), config)
+    }
+
+    fn deepen<P: TwistyPuzzle, F: Fn(&P) -> bool>(
+        start: &P,
+        is_target: &F,
+        moves: &[P::Move],
+        config: &SearchConfig,
+    ) -> Result<Vec<P::Move>, SearchError> {
+        let mut budget = Budget::new(config);
+        let mut state = start.clone();
+        for solution_length in 0..=config.max_depth {
+            budget.deepen(solution_length)?;
+            let mut discovered = HashSet::new();
+            let mut move_stack = vec![];
+            if Self::search(
+                &mut state,
+                is_target,
+                &mut move_stack,
+                &mut discovered,
+                solution_length,
+                moves,
+                &mut budget,
+            )? {
+                return Ok(move_stack);
+            }
+        }
+        Err(SearchError::NotFound)
+    }
+}
+
+impl Solver for Iddfs {
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        reachable(start, target, &config.moves)?;
+        self.solve_puzzle(start, target, &config.moves.moves(), config)
+    }
+}
+
+// Walks downhill through a precomputed table of every state's distance. Building the table
+// takes a moment, after which every solve is instant.
+pub struct TableLookup {
+    table: DistanceTable,
+}
+
+impl Default for TableLookup {
+    fn default() -> TableLookup { TableLookup::new() }
+}
+
+impl TableLookup {
+    pub fn new() -> TableLookup {
+        TableLookup::from_table(DistanceTable::build_parallel(default_threads()))
+    }
+    // Only solves with the given moves, which makes its table much quicker to build if they
+    // generate a small subgroup.
+    pub fn with_moves(moves: &MoveSet) -> TableLookup {
+        TableLookup::from_table(DistanceTable::build_parallel_with(
+            &moves.moves(),
+            default_threads(),
+        ))
+    }
+    // The same as with_moves, but the table is only built the first time the moves are asked for
+    // and kept for the rest of the program. The lock is only held to find the moves' slot, so
+    // building one table doesn't hold up callers wanting another.
+    pub fn shared(moves: &MoveSet) -> Arc<TableLookup> {
+        type Slot = Arc<OnceLock<Arc<TableLookup>>>;
+        static TABLES: OnceLock<Mutex<HashMap<MoveSet, Slot>>> = OnceLock::new();
+        let slot = TABLES
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap()
+            .entry(moves.clone())
+            .or_default()
+            .clone();
+        slot.get_or_init(|| Arc::new(TableLookup::with_moves(moves)))
+            .clone()
+    }
+    pub fn from_table(table: DistanceTable) -> TableLookup { TableLookup { table } }
+    pub fn table(&self) -> &DistanceTable { &self.table }
+}
+
+impl TableLookup {
+    // The state relative to target to walk down from, and how far down it is.
+    fn descent(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<(NormalizedSkewb, usize), SearchError> {
+        if start.center_orientations.is_some() || target.center_orientations.is_some() {
+            return Err(SearchError::UnsupportedSupercube);
+        }
+        let table_moves = self.table.moves();
+        let allowed = config.moves.moves();
+        if allowed.len() != table_moves.len() || allowed.iter().any(|m| !table_moves.contains(m)) {
+            return Err(SearchError::UnsupportedMoves);
+        }
+        let state = start.relative_to(target);
+        let distance = self
+            .table
+            .distance(&state)
+            .ok_or(SearchError::OutsideSubgroup)?;
+        if distance > config.max_depth {
+            return Err(SearchError::NotFound);
+        }
+        Ok((state, distance))
+    }
+
+    // Every shortest solution, in the order solve_to would consider them.
+    pub fn all_solutions(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Vec<Move>>, SearchError> {
+        let mut budget = Budget::new(config);
+        let (mut state, distance) = self.descent(start, target, config)?;
+        budget.deepen(distance)?;
+        let mut solutions = vec![];
+        self.collect(
+            &mut state,
+            distance,
+            &mut vec![],
+            &mut solutions,
+            &mut budget,
+        )?;
+        Ok(solutions)
+    }
+
+    fn collect(
+        &self,
+        state: &mut NormalizedSkewb,
+        distance: usize,
+        move_stack: &mut Vec<Move>,
+        solutions: &mut Vec<Vec<Move>>,
+        budget: &mut Budget,
+    ) -> Result<(), SearchError> {
+        if distance == 0 {
+            solutions.push(move_stack.clone());
+            return Ok(());
+        }
+        budget.expand()?;
+        for move_ in self.table.moves().iter() {
+            state.do_move(move_);
+            if self.table.distance(state) == Some(distance - 1) {
+                move_stack.push(*move_);
+                let result = self.collect(state, distance - 1, move_stack, solutions, budget);
+                move_stack.pop();
+                result?;
+            }
+            state.undo_move(move_);
+        }
+        Ok(())
+    }
+}
+
+impl Solver for TableLookup {
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        let mut budget = Budget::new(config);
+        let (mut state, mut distance) = self.descent(start, target, config)?;
+        budget.deepen(distance)?;
+
+        let mut solution = vec![];
+        while distance > 0 {
+            budget.expand()?;
+            for move_ in self.table.moves().iter() {
+                state.do_move(move_);
+                if self.table.distance(&state) == Some(distance - 1) {
+                    solution.push(*move_);
+                    distance -= 1;
+                    break;
+                }
+                state.undo_move(move_);
+            }
+        }
+        Ok(solution)
+    }
+}
+
+type CornerKey = ([Orientation; 4], [usize; 4], [Orientation; 4]);
+
+// Distances to solved of the states of a projection of the puzzle. Turns act on the projection
+// independently of what was projected away, so it can be searched through representatives.
+fn pattern_database<K, F>(key: F) -> HashMap<K, usize>
+where
+    K: Eq + Hash,
+    F: Fn(&NormalizedSkewb) -> K,
+{
+    let mut distances = HashMap::new();
+    let solved = NormalizedSkewb::new();
+    distances.insert(key(&solved), 0);
+    let mut frontier = vec![solved];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        let mut next = vec![];
+        for state in frontier.iter() {
+            for move_ in Move::all() {
+                let mut neighbor = state.clone();
+                neighbor.do_move(&move_);
+                let k = key(&neighbor);
+                if let Entry::Vacant(entry) = distances.entry(k) {
+                    entry.insert(depth + 1);
+                    next.push(neighbor);
+                }
+            }
+        }
+        frontier = next;
+        depth += 1;
+    }
+    distances
+}
+
+// IDA* guided by how far the corners and the centers each are from solved on their own.
+pub struct IdaStar {
+    corners: HashMap<CornerKey, usize>,
+    centers: HashMap<[Color; 6], usize>,
+}
+
+impl Default for IdaStar {
+    fn default() -> IdaStar { IdaStar::new() }
+}
+
+enum Bound {
+    Found,
+    Exceeded(usize),
+}
+
+impl IdaStar {
+    pub fn new() -> IdaStar {
+        IdaStar {
+            corners: pattern_database(Self::corner_key),
+            centers: pattern_database(|s| s.center_pieces),
+        }
+    }
+
+    // One IdaStar for the whole program, built the first time it's asked for.
+    pub fn shared() -> &'static IdaStar {
+        static SHARED: OnceLock<IdaStar> = OnceLock::new();
+        SHARED.get_or_init(IdaStar::new)
+    }
+
+    fn corner_key(s: &NormalizedSkewb) -> CornerKey {
+        (
+            s.fixed_orientations,
+            s.floating_pieces,
+            s.floating_orientations,
+        )
+    }
+
+    fn heuristic(&self, state: &NormalizedSkewb) -> usize {
+        let corners = self.corners[&Self::corner_key(state)];
+        let centers = self.centers[&state.center_pieces];
+        corners.max(centers)
+    }
+
+    fn search(
+        &self,
+        state: &mut NormalizedSkewb,
+        move_stack: &mut Vec<Move>,
+        bound: usize,
+        moves: &[Move],
+        budget: &mut Budget,
+    ) -> Result<Bound, SearchError> {
+        let estimate = move_stack.len() + self.heuristic(state);
+        if estimate > bound {
+            return Ok(Bound::Exceeded(estimate));
+        } else if state.is_solved() {
+            return Ok(Bound::Found);
+        }
+        budget.expand()?;
+
+        let mut next_bound = usize::MAX;
+        for move_ in moves.iter() {
+            if <NormalizedSkewb as TwistyPuzzle>::redundant(move_stack, move_, moves) {
+                continue;
+            }
+            state.do_move(move_);
+            move_stack.push(*move_);
+            let result = self.search(state, move_stack, bound, moves, budget);
+            state.undo_move(move_);
+            match result? {
+                Bound::Found => return Ok(Bound::Found),
+                Bound::Exceeded(b) => next_bound = next_bound.min(b),
+            }
+            move_stack.pop();
+        }
+        Ok(Bound::Exceeded(next_bound))
+    }
+}
+
+impl Solver for IdaStar {
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        reachable(start, target, &config.moves)?;
+        let mut budget = Budget::new(config);
+        let moves = config.moves.moves();
+        let mut state = start.relative_to(target);
+        let mut bound = self.heuristic(&state);
+        while bound <= config.max_depth {
+            budget.deepen(bound)?;
+            let mut move_stack = vec![];
+            match self.search(&mut state, &mut move_stack, bound, &moves, &mut budget)? {
+                Bound::Found => return Ok(move_stack),
+                Bound::Exceeded(b) => bound = b,
+            }
+        }
+        Err(SearchError::NotFound)
+    }
+}
+
+// Breadth-first search from both ends at once, which only has to go half as deep as a search
+// from one end.
+pub struct Bidirectional;
+
+impl Bidirectional {
+    // Expands the next layer of one side of the search. Each state remembers the move that
+    // connects it to the state it was reached from, which the backward side has to undo since
+    // its moves are used in reverse. Returns a state both sides have reached, if there is one.
+    fn expand(
+        frontier: &mut Vec<NormalizedSkewb>,
+        reached: &mut HashMap<NormalizedSkewb, Option<Move>>,
+        other: &HashMap<NormalizedSkewb, Option<Move>>,
+        backward: bool,
+        moves: &[Move],
+        budget: &mut Budget,
+    ) -> Result<Option<NormalizedSkewb>, SearchError> {
+        let mut next = vec![];
+        for state in frontier.iter() {
+            budget.expand()?;
+            let last = reached[state];
+            for move_ in moves.iter() {
+                if <NormalizedSkewb as TwistyPuzzle>::redundant(last.as_slice(), move_, moves) {
+                    continue;
+                }
+                let mut neighbor = state.clone();
+                if backward {
+                    neighbor.undo_move(move_);
+                } else {
+                    neighbor.do_move(move_);
+                }
+                if reached.contains_key(&neighbor) {
+                    continue;
+                }
+                reached.insert(neighbor.clone(), Some(*move_));
+                if other.contains_key(&neighbor) {
+                    return Ok(Some(neighbor));
+                }
+                next.push(neighbor);
+            }
+        }
+        *frontier = next;
+        Ok(None)
+    }
+
+    // The moves between state and the root of its side of the search, in the order they're
+    // walked back to the root.
+    fn path(
+        mut state: NormalizedSkewb,
+        reached: &HashMap<NormalizedSkewb, Option<Move>>,
+        backward: bool,
+    ) -> Vec<Move> {
+        let mut moves = vec![];
+        while let Some(move_) = reached[&state] {
+            if backward {
+                state.do_move(&move_);
+            } else {
+                state.undo_move(&move_);
+            }
+            moves.push(move_);
+        }
+        moves
+    }
+}
+
+impl Solver for Bidirectional {
+    fn solve_to(
+        &self,
+        start: &NormalizedSkewb,
+        target: &NormalizedSkewb,
+        config: &SearchConfig,
+    ) -> Result<Vec<Move>, SearchError> {
+        reachable(start, target, &config.moves)?;
+        let mut budget = Budget::new(config);
+        let moves = config.moves.moves();
+        if start == target {
+            return Ok(vec![]);
+        }
+
+        let mut forward = HashMap::new();
+        forward.insert(start.clone(), None);
+        let mut forward_frontier = vec![start.clone()];
+        let mut backward = HashMap::new();
+        backward.insert(target.clone(), None);
+        let mut backward_frontier = vec![target.clone()];
+
+        for depth in 1..=config.max_depth {
+            budget.deepen(depth)?;
+            let meeting = if forward_frontier.len() <= backward_frontier.len() {
+                Self::expand(
+                    &mut forward_frontier,
+                    &mut forward,
+                    &backward,
+                    false,
+                    &moves,
+                    &mut budget,
+                )?
+            } else {
+                Self::expand(
+                    &mut backward_frontier,
+                    &mut backward,
+                    &forward,
+                    true,
+                    &moves,
+                    &mut budget,
+                )?
+            };
+            if let Some(meeting) = meeting {
+                let mut solution = Self::path(meeting.clone(), &forward, false);
+                solution.reverse();
+                solution.extend(Self::path(meeting, &backward, true));
+                return Ok(solution);
+            }
+            // One side has run out of states without meeting the other, so it has seen everything
+            // the allowed moves can reach.
+            if forward_frontier.is_empty() || backward_frontier.is_empty() {
+                return Err(SearchError::OutsideSubgroup);
+            }
+        }
+        Err(SearchError::NotFound)
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn scramble_moves(seed: u64, length: usize) -> Vec<Move> {
+    let moves = Move::all();
+    let mut x = seed;
+    (0..length)
+        .map(|_| {
+            x = x
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            moves[(x >> 33) as usize % moves.len()]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) fn scramble(seed: u64, length: usize) -> NormalizedSkewb {
+    NormalizedSkewb::after(&scramble_moves(seed, length))
+}
+
+#[test]
+fn backends_agree() {
+    let config = SearchConfig::default();
+    let table = TableLookup::new();
+    let ida = IdaStar::new();
+    for seed in 0..10 {
+        let start = scramble(seed, 5);
+        let expected = Iddfs.solve(&start, &config).unwrap();
+        assert!(start.applied(&expected).is_solved());
+
+        let solvers: [&dyn Solver; 3] = [&table, &ida, &Bidirectional];
+        for solver in solvers.iter() {
+            let solution = solver.solve(&start, &config).unwrap();
+            assert_eq!(expected.len(), solution.len());
+            assert!(start.applied(&solution).is_solved());
+        }
+    }
+
+    // A supercube can't be solved to a puzzle that isn't one, or the other way round.
+    let config = SearchConfig {
+        max_depth: 6,
+        ..SearchConfig::default()
+    };
+    let supercube = NormalizedSkewb::supercube();
+    let plain = NormalizedSkewb::new();
+    let solvers: [&dyn Solver; 4] = [&Iddfs, &table, &ida, &Bidirectional];
+    for solver in solvers.iter() {
+        for (start, target) in [(&supercube, &plain), (&plain, &supercube)].iter() {
+            assert_eq!(
+                Err(SearchError::UnsupportedSupercube),
+                solver.solve_to(start, target, &config)
+            );
+        }
+    }
+}
+
+#[test]
+fn long_solutions() {
+    let config = SearchConfig::default();
+    let table = TableLookup::new();
+    let ida = IdaStar::new();
+    for seed in 0..5 {
+        let start = scramble(seed, 30);
+        let expected = table.solve(&start, &config).unwrap();
+        assert!(start.applied(&expected).is_solved());
+        assert_eq!(table.table().distance(&start), Some(expected.len()));
+
+        let solvers: [&dyn Solver; 2] = [&ida, &Bidirectional];
+        for solver in solvers.iter() {
+            let solution = solver.solve(&start, &config).unwrap();
+            assert_eq!(expected.len(), solution.len());
+            assert!(start.applied(&solution).is_solved());
+        }
+    }
+}
+
+#[test]
+fn solve_to_target() {
+    let config = SearchConfig::default();
+    let table = TableLookup::new();
+    let ida = IdaStar::new();
+    let target = scramble(2, 20);
+    let start = target.applied(&scramble_moves(1, 5));
+    let solvers: [&dyn Solver; 4] = [&Iddfs, &table, &ida, &Bidirectional];
+    let expected = Bidirectional.solve_to(&start, &target, &config).unwrap();
+    for solver in solvers.iter() {
+        let solution = solver.solve_to(&start, &target, &config).unwrap();
+        assert_eq!(expected.len(), solution.len());
+        assert_eq!(target, start.applied(&solution));
+    }
+}
+
+#[test]
+fn limits_are_respected() {
+    let start = scramble(3, 30);
+    let table = TableLookup::new();
+    let distance = table.table().distance(&start).unwrap();
+    let config = SearchConfig {
+        max_depth: distance - 1,
+        ..SearchConfig::default()
+    };
+    let solvers: [&dyn Solver; 3] = [&table, &IdaStar::new(), &Bidirectional];
+    for solver in solvers.iter() {
+        assert_eq!(Err(SearchError::NotFound), solver.solve(&start, &config));
+    }
+
+    let config = SearchConfig {
+        node_limit: Some(100),
+        ..SearchConfig::default()
+    };
+    let solvers: [&dyn Solver; 3] = [&Iddfs, &IdaStar::new(), &Bidirectional];
+    for solver in solvers.iter() {
+        assert_eq!(
+            Err(SearchError::NodeLimitReached),
+            solver.solve(&start, &config)
+        );
+    }
+
+    let config = SearchConfig {
+        deadline: Some(Instant::now()),
+        ..SearchConfig::default()
+    };
+    assert_eq!(
+        Err(SearchError::DeadlinePassed),
+        Iddfs.solve(&start, &config)
+    );
+}
+
+#[test]
+fn progress_and_cancellation() {
+    use std::sync::Mutex;
+
+    let start = scramble(4, 30);
+    let cancellation = CancellationToken::new();
+    let reports = Arc::new(Mutex::new(vec![]));
+    let config = SearchConfig {
+        progress: Some(Box::new({
+            let cancellation = cancellation.clone();
+            let reports = reports.clone();
+            move |progress: Progress| {
+                reports.lock().unwrap().push(progress);
+                if progress.depth == 3 {
+                    cancellation.cancel();
+                }
+            }
+        })),
+        cancellation: Some(cancellation.clone()),
+        ..SearchConfig::default()
+    };
+    assert_eq!(Err(SearchError::Cancelled), Iddfs.solve(&start, &config));
+
+    let reports = reports.lock().unwrap();
+    let depths: Vec<usize> = reports.iter().map(|p| p.depth).collect();
+    assert_eq!(vec![0, 1, 2, 3], depths);
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].nodes_expanded <= w[1].nodes_expanded));
+}
+
+#[test]
+fn restricted_moves() {
+    use skewb::Direction;
+
+    let front = MoveSet::new(&[(0, 1, 1), (1, 0, 1)], None);
+    let config = SearchConfig {
+        moves: front.clone(),
+        ..SearchConfig::default()
+    };
+    let table = TableLookup::with_moves(&front);
+    let ida = IdaStar::new();
+    for seed in 0..5 {
+        let scramble: Vec<Move> = scramble_moves(seed, 6)
+            .into_iter()
+            .filter(|m| front.contains(m))
+            .collect();
+        let start = NormalizedSkewb::after(&scramble);
+        let expected = table.solve(&start, &config).unwrap();
+        assert!(expected.iter().all(|m| front.contains(m)));
+        assert!(start.applied(&expected).is_solved());
+
+        let solvers: [&dyn Solver; 3] = [&Iddfs, &ida, &Bidirectional];
+        for solver in solvers.iter() {
+            let solution = solver.solve(&start, &config).unwrap();
+            assert_eq!(expected.len(), solution.len());
+            assert!(solution.iter().all(|m| front.contains(m)));
+            assert!(start.applied(&solution).is_solved());
+        }
+    }
+
+    let mut outside = NormalizedSkewb::new();
+    outside.turn_lr((0, 0, 0));
+    let solvers: [&dyn Solver; 4] = [&Iddfs, &table, &ida, &Bidirectional];
+    for solver in solvers.iter() {
+        assert_eq!(
+            Err(SearchError::OutsideSubgroup),
+            solver.solve(&outside, &config)
+        );
+    }
+    let config = SearchConfig {
+        moves: MoveSet::new(&[(0, 1, 1)], None),
+        ..SearchConfig::default()
+    };
+    assert_eq!(
+        Err(SearchError::OutsideSubgroup),
+        Bidirectional.solve(&outside, &config)
+    );
+    assert_eq!(
+        Err(SearchError::UnsupportedMoves),
+        table.solve(&outside, &SearchConfig::default())
+    );
+
+    let mut one_way = NormalizedSkewb::new();
+    one_way.turn_lr((0, 0, 0));
+    one_way.turn_lr((0, 0, 0));
+    let config = SearchConfig {
+        moves: MoveSet::new(&[(0, 0, 0)], Some(Direction::LR)),
+        ..SearchConfig::default()
+    };
+    let expected = vec![Move::new((0, 0, 0), Direction::LR)];
+    let solvers: [&dyn Solver; 3] = [&Iddfs, &ida, &Bidirectional];
+    for solver in solvers.iter() {
+        assert_eq!(Ok(expected.clone()), solver.solve(&one_way, &config));
+    }
+    let expected = vec![Move::new((0, 0, 0), Direction::LR); 2];
+    one_way.turn_lr((0, 0, 0));
+    one_way.turn_lr((0, 0, 0));
+    for solver in solvers.iter() {
+        assert_eq!(Ok(expected.clone()), solver.solve(&one_way, &config));
+    }
+}
+
+#[test]
+fn supercube_solutions() {
+    use skewb::Direction;
+
+    let config = SearchConfig::default();
+    let start = NormalizedSkewb::supercube().applied(&scramble_moves(4, 6));
+    let expected = Bidirectional.solve(&start, &config).unwrap();
+    assert!(start.applied(&expected).is_solved());
+    for solver in [&Iddfs as &dyn Solver, &IdaStar::new()].iter() {
+        let solution = solver.solve(&start, &config).unwrap();
+        assert_eq!(expected.len(), solution.len());
+        assert!(start.applied(&solution).is_solved());
+    }
+    assert_eq!(
+        Err(SearchError::UnsupportedSupercube),
+        TableLookup::with_moves(&MoveSet::new(&[(0, 0, 0)], None)).solve(&start, &config)
+    );
+
+    // Every piece back where it started, but four centres turned half way round.
+    let moves = [
+        Move::new((1, 0, 1), Direction::FB),
+        Move::new((0, 0, 0), Direction::FB),
+        Move::new((1, 1, 0), Direction::FB),
+    ];
+    let mut twisted = NormalizedSkewb::supercube();
+    for _ in 0..12 {
+        twisted = twisted.applied(&moves);
+    }
+    assert!(!twisted.is_solved());
+    assert!(NormalizedSkewb {
+        center_orientations: None,
+        ..twisted.clone()
+    }
+    .is_solved());
+    let solution = Bidirectional.solve(&twisted, &config).unwrap();
+    assert_eq!(12, solution.len());
+    assert!(twisted.applied(&solution).is_solved());
+}
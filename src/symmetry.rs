@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use rotation::{Frame, Rotation};
+use skewb::{NormalizedSkewb, Orientation, Skewb, CORNER_STATES, STATE_COUNT};
+
+// One of the 48 symmetries of the cube: holding the puzzle another way round, possibly after
+// reflecting it across the plane between U and D. Every reflection is one of these, since the
+// other planes are that one turned round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Symmetry {
+    pub frame: Frame,
+    pub mirrored: bool,
+}
+
+impl Symmetry {
+    pub fn identity() -> Symmetry {
+        Symmetry {
+            frame: Frame::identity(),
+            mirrored: false,
+        }
+    }
+
+    // Every symmetry, starting with the identity.
+    pub fn all() -> Vec<Symmetry> {
+        let mut symmetries = vec![];
+        for &mirrored in [false, true].iter() {
+            for &frame in Frame::all().iter() {
+                symmetries.push(Symmetry { frame, mirrored });
+            }
+        }
+        symmetries
+    }
+}
+
+// A symmetry made ready to apply to many states: the rotations that make up its frame, and the
+// solved puzzle and supercube held that way round.
+struct Held {
+    mirrored: bool,
+    rotations: Vec<Rotation>,
+    solved: Skewb,
+    supercube: Skewb,
+}
+
+impl Held {
+    fn new(symmetry: &Symmetry) -> Held {
+        let rotations = symmetry.frame.rotations();
+        let mut solved = Skewb::new();
+        let mut supercube = Skewb::supercube();
+        for &rotation in rotations.iter() {
+            solved.rotate(rotation);
+            supercube.rotate(rotation);
+        }
+        Held {
+            mirrored: symmetry.mirrored,
+            rotations,
+            solved,
+            supercube,
+        }
+    }
+
+    fn apply(&self, state: &NormalizedSkewb) -> NormalizedSkewb {
+        let mut skewb = state.clone().denormalize();
+        if self.mirrored {
+            skewb = skewb.mirror(Orientation::UD);
+        }
+        for &rotation in self.rotations.iter() {
+            skewb.rotate(rotation);
+        }
+        // A symmetry that swaps fixed and floating corners leaves the fixed ones out of place, so
+        // turn the whole puzzle back round first.
+        let solved = if state.center_orientations.is_some() {
+            &self.supercube
+        } else {
+            &self.solved
+        };
+        let mut relative = skewb.relative_to(solved);
+        relative.rotate_to_canonical();
+        relative.normalize()
+    }
+}
+
+impl NormalizedSkewb {
+    // The same case seen through the symmetry, with the colours renamed so that a solved puzzle
+    // still looks solved: the state reached by doing the moves that reach self mirrored and then
+    // carried by the frame.
+    pub fn symmetric(&self, symmetry: &Symmetry) -> NormalizedSkewb {
+        Held::new(symmetry).apply(self)
+    }
+
+    // The representative of the states that are the same case as self under rotation, reflection
+    // and renaming the colours: the one among them with the lowest rank, and on a supercube the
+    // least centre orientations among those.
+    pub fn canonical(&self) -> NormalizedSkewb {
+        Symmetry::all()
+            .iter()
+            .map(|symmetry| self.symmetric(symmetry))
+            .min_by_key(|state| (state.rank(), state.center_orientations))
+            .unwrap()
+    }
+}
+
+// Where each symmetry takes every rank, looked up separately for the corner and centre halves of
+// the rank the way MoveTable does for moves. A symmetry takes states with solved corners to states
+// with solved corners and likewise for centres, but one that swaps fixed and floating corners has
+// to be followed by a rotation back into the canonical position, and which rotation that is
+// depends on the corners. So the corners also pick which row of centre ranks to look in.
+// There are 8748 corner ranks and 360 centre ranks, so they all fit in a u16.
+pub(crate) struct SymmetryTable {
+    corners: Vec<u16>,
+    rows: Vec<u16>,
+    centers: Vec<u16>,
+}
+
+const CENTER_STATES: usize = STATE_COUNT / CORNER_STATES;
+
+impl SymmetryTable {
+    pub(crate) fn new() -> SymmetryTable {
+        let mut corners = Vec::with_capacity(48 * CORNER_STATES);
+        let mut rows = Vec::with_capacity(48 * CORNER_STATES);
+        let mut centers = vec![];
+        for symmetry in Symmetry::all().iter() {
+            let held = Held::new(symmetry);
+            // The corner states that need the same rotation leave solved centres the same way.
+            let mut row_of_centers = HashMap::new();
+            for corner_rank in 0..CORNER_STATES {
+                let rank = held.apply(&NormalizedSkewb::from_rank(corner_rank)).rank();
+                corners.push((rank % CORNER_STATES) as u16);
+                let row = *row_of_centers
+                    .entry(rank / CORNER_STATES)
+                    .or_insert_with(|| {
+                        for center_rank in 0..CENTER_STATES {
+                            let state = NormalizedSkewb::from_rank(
+                                center_rank * CORNER_STATES + corner_rank,
+                            );
+                            centers.push((held.apply(&state).rank() / CORNER_STATES) as u16);
+                        }
+                        (centers.len() / CENTER_STATES - 1) as u16
+                    });
+                rows.push(row);
+            }
+        }
+        SymmetryTable {
+            corners,
+            rows,
+            centers,
+        }
+    }
+
+    // The rank of NormalizedSkewb::canonical for the state with the given rank.
+    pub(crate) fn canonical_rank(&self, rank: usize) -> usize {
+        (0..self.corners.len() / CORNER_STATES)
+            .map(|i| {
+                let corners = self.corners[i * CORNER_STATES + rank % CORNER_STATES] as usize;
+                let row = self.rows[i * CORNER_STATES + rank % CORNER_STATES] as usize;
+                let centers = self.centers[row * CENTER_STATES + rank / CORNER_STATES] as usize;
+                centers * CORNER_STATES + corners
+            })
+            .min()
+            .unwrap()
+    }
+}
+
+// The states of a set that are the same case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymmetryClass {
+    pub canonical: NormalizedSkewb,
+    // The states of the set in the class, in the order they came.
+    pub members: Vec<NormalizedSkewb>,
+}
+
+// The distinct cases among some states, in the order their first states came.
+pub fn symmetry_classes<I>(states: I) -> Vec<SymmetryClass>
+where I: IntoIterator<Item = NormalizedSkewb> {
+    let mut classes: Vec<SymmetryClass> = vec![];
+    let mut index = HashMap::new();
+    for state in states {
+        let canonical = state.canonical();
+        let i = *index.entry(canonical.clone()).or_insert_with(|| {
+            classes.push(SymmetryClass {
+                canonical,
+                members: vec![],
+            });
+            classes.len() - 1
+        });
+        classes[i].members.push(state);
+    }
+    classes
+}
+
+#[test]
+fn symmetries_carry_moves() {
+    use notation::fixed_corner_form;
+    use skewb::Move;
+    use solver::scramble_moves;
+
+    assert_eq!(48, Symmetry::all().len());
+    for seed in 0..5 {
+        let moves = scramble_moves(seed, 10);
+        let state = NormalizedSkewb::after(&moves);
+        assert_eq!(state, state.symmetric(&Symmetry::identity()));
+        for symmetry in Symmetry::all().iter() {
+            let carried: Vec<Move> = moves
+                .iter()
+                .map(|m| {
+                    let m = if symmetry.mirrored {
+                        m.mirror(Orientation::UD)
+                    } else {
+                        *m
+                    };
+                    symmetry.frame.carry(&m)
+                })
+                .collect();
+            let expected = NormalizedSkewb::after(&fixed_corner_form(&carried).0);
+            assert_eq!(expected, state.symmetric(symmetry));
+        }
+    }
+}
+
+#[test]
+fn same_case_same_canonical() {
+    use solver::scramble;
+
+    for seed in 0..5 {
+        let state = scramble(seed, 15);
+        let canonical = state.canonical();
+        assert_eq!(canonical, canonical.canonical());
+        for symmetry in Symmetry::all().iter() {
+            assert_eq!(canonical, state.symmetric(symmetry).canonical());
+        }
+    }
+}
+
+#[test]
+fn supercube_symmetries() {
+    use notation::fixed_corner_form;
+    use skewb::Move;
+    use solver::scramble_moves;
+
+    let supercube = NormalizedSkewb::supercube();
+    for seed in 0..5 {
+        let moves = scramble_moves(seed, 10);
+        let state = supercube.applied(&moves);
+        assert_eq!(state, state.symmetric(&Symmetry::identity()));
+        let canonical = state.canonical();
+        assert!(canonical.center_orientations.is_some());
+        for symmetry in Symmetry::all().iter() {
+            let carried: Vec<Move> = moves
+                .iter()
+                .map(|m| {
+                    let m = if symmetry.mirrored {
+                        m.mirror(Orientation::UD)
+                    } else {
+                        *m
+                    };
+                    symmetry.frame.carry(&m)
+                })
+                .collect();
+            let expected = supercube.applied(&fixed_corner_form(&carried).0);
+            assert_eq!(expected, state.symmetric(symmetry));
+            assert_eq!(canonical, expected.canonical());
+        }
+    }
+
+    // Turning centres in place is a different case from leaving them.
+    let mut twisted = NormalizedSkewb::supercube();
+    twisted.center_orientations = Some([2, 2, 2, 2, 0, 0]);
+    assert!(twisted.canonical() != supercube.canonical());
+    assert_eq!(supercube, supercube.canonical());
+}
+
+#[test]
+fn one_move_is_one_case() {
+    use skewb::Move;
+
+    let one_move = Move::all()
+        .into_iter()
+        .map(|m| NormalizedSkewb::after(&[m]));
+    let classes = symmetry_classes(one_move);
+    assert_eq!(1, classes.len());
+    assert_eq!(8, classes[0].members.len());
+
+    let two_moves: Vec<NormalizedSkewb> = Move::all()
+        .iter()
+        .flat_map(|&a| {
+            Move::all()
+                .into_iter()
+                .map(move |b| NormalizedSkewb::after(&[a, b]))
+        })
+        .collect();
+    let classes = symmetry_classes(two_moves.iter().cloned());
+    assert!(classes.len() > 1 && classes.len() < two_moves.len());
+    assert_eq!(
+        two_moves.len(),
+        classes.iter().map(|c| c.members.len()).sum::<usize>()
+    );
+    for class in classes.iter() {
+        for member in class.members.iter() {
+            assert_eq!(class.canonical, member.canonical());
+        }
+    }
+}
+
+#[test]
+fn table_agrees_with_canonical() {
+    use solver::scramble;
+
+    let table = SymmetryTable::new();
+    for seed in 0..20 {
+        let state = scramble(seed, 12);
+        assert_eq!(state.canonical().rank(), table.canonical_rank(state.rank()));
+    }
+}
@@ -0,0 +1,428 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use batch::default_threads;
+use skewb::{Corner, Move, MoveSet, NormalizedSkewb, CORNER_STATES, STATE_COUNT};
+use solver::SearchError;
+use symmetry::SymmetryTable;
+
+// Marks a state the breadth-first search never got to.
+const UNREACHED: u8 = u8::MAX;
+
+// Where each move takes every rank, looked up separately for the corner and center halves of the
+// rank so that searches over the whole puzzle don't have to build a NormalizedSkewb per state.
+pub(crate) struct MoveTable {
+    moves: Vec<Move>,
+    corners: Vec<usize>,
+    centers: Vec<usize>,
+}
+
+impl MoveTable {
+    pub(crate) fn new(moves: &[Move]) -> MoveTable {
+        let mut corners = Vec::with_capacity(CORNER_STATES * moves.len());
+        for corner_rank in 0..CORNER_STATES {
+            let mut state = NormalizedSkewb::from_rank(corner_rank);
+            for move_ in moves.iter() {
+                state.do_move(move_);
+                corners.push(state.rank() % CORNER_STATES);
+                state.undo_move(move_);
+            }
+        }
+
+        let mut centers = Vec::with_capacity(STATE_COUNT / CORNER_STATES * moves.len());
+        for center_rank in 0..STATE_COUNT / CORNER_STATES {
+            let mut state = NormalizedSkewb::from_rank(center_rank * CORNER_STATES);
+            for move_ in moves.iter() {
+                state.do_move(move_);
+                centers.push(state.rank() / CORNER_STATES);
+                state.undo_move(move_);
+            }
+        }
+
+        MoveTable {
+            moves: moves.to_vec(),
+            corners,
+            centers,
+        }
+    }
+
+    pub(crate) fn moves(&self) -> &[Move] { &self.moves }
+
+    // The rank of the state the i'th move takes the state with the given rank to.
+    pub(crate) fn apply(&self, rank: usize, i: usize) -> usize {
+        let n = self.moves.len();
+        let corners = self.corners[rank % CORNER_STATES * n + i];
+        let centers = self.centers[rank / CORNER_STATES * n + i];
+        centers * CORNER_STATES + corners
+    }
+}
+
+// The optimal distance from every state to the solved state, indexed by NormalizedSkewb::rank.
+// A table built from only some of the moves covers just the subgroup they generate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistanceTable {
+    moves: Vec<Move>,
+    distances: Vec<u8>,
+}
+
+impl DistanceTable {
+    pub fn build() -> DistanceTable { DistanceTable::build_with(&Move::all()) }
+
+    // The search runs backwards from solved, so it turns each corner the opposite way. That only
+    // matters if just one direction is allowed.
+    fn inverse_moves(moves: &[Move]) -> MoveTable {
+        let inverses: Vec<Move> = moves.iter().map(Move::inverse).collect();
+        MoveTable::new(&inverses)
+    }
+
+    pub fn build_with(moves: &[Move]) -> DistanceTable {
+        let move_table = Self::inverse_moves(moves);
+        let mut distances = vec![UNREACHED; STATE_COUNT];
+
+        let solved = NormalizedSkewb::new().rank();
+        distances[solved] = 0;
+        let mut frontier = vec![solved];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            let mut next = vec![];
+            for &rank in frontier.iter() {
+                for i in 0..move_table.moves().len() {
+                    let r = move_table.apply(rank, i);
+                    if distances[r] == UNREACHED {
+                        distances[r] = depth + 1;
+                        next.push(r);
+                    }
+                }
+            }
+            frontier = next;
+            depth += 1;
+        }
+
+        DistanceTable {
+            moves: moves.to_vec(),
+            distances,
+        }
+    }
+
+    pub fn build_parallel(threads: usize) -> DistanceTable {
+        DistanceTable::build_parallel_with(&Move::all(), threads)
+    }
+
+    // The same table as build_with, but each layer of the search is expanded by several threads,
+    // which claim states by setting their bit in a shared visited bitmap.
+    pub fn build_parallel_with(moves: &[Move], threads: usize) -> DistanceTable {
+        let move_table = &Self::inverse_moves(moves);
+        let visited: Vec<AtomicU64> = (0..STATE_COUNT.div_ceil(64))
+            .map(|_| AtomicU64::new(0))
+            .collect();
+        let visit = &|rank: usize| {
+            let bit = 1 << (rank % 64);
+            visited[rank / 64].fetch_or(bit, Ordering::Relaxed) & bit == 0
+        };
+        let mut distances = vec![UNREACHED; STATE_COUNT];
+
+        let solved = NormalizedSkewb::new().rank();
+        visit(solved);
+        distances[solved] = 0;
+        let mut frontier = vec![solved];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            let chunk_size = frontier.len().div_ceil(threads.max(1));
+            let next: Vec<usize> = thread::scope(|scope| {
+                let workers: Vec<_> = frontier
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            let mut next = vec![];
+                            for &rank in chunk {
+                                for i in 0..move_table.moves().len() {
+                                    let r = move_table.apply(rank, i);
+                                    if visit(r) {
+                                        next.push(r);
+                                    }
+                                }
+                            }
+                            next
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect()
+            });
+            for &r in next.iter() {
+                distances[r] = depth + 1;
+            }
+            frontier = next;
+            depth += 1;
+        }
+
+        DistanceTable {
+            moves: moves.to_vec(),
+            distances,
+        }
+    }
+
+    // The moves the table was built from.
+    pub fn moves(&self) -> &[Move] { &self.moves }
+
+    // One byte per state, in rank order.
+    pub fn as_bytes(&self) -> &[u8] { &self.distances }
+
+    pub fn distance(&self, skewb: &NormalizedSkewb) -> Option<usize> {
+        match self.distances[skewb.rank()] {
+            UNREACHED => None,
+            d => Some(d as usize),
+        }
+    }
+
+    pub fn diameter(&self) -> usize {
+        self.distances
+            .iter()
+            .filter(|&&d| d != UNREACHED)
+            .map(|&d| d as usize)
+            .max()
+            .unwrap()
+    }
+
+    pub fn report(&self) -> DepthReport {
+        let diameter = self.diameter();
+        let mut histogram = vec![0; diameter + 1];
+        let mut antipodes = vec![];
+        for (rank, &d) in self.distances.iter().enumerate() {
+            if d == UNREACHED {
+                continue;
+            }
+            histogram[d as usize] += 1;
+            if d as usize == diameter {
+                antipodes.push(NormalizedSkewb::from_rank(rank));
+            }
+        }
+        DepthReport {
+            group_size: histogram.iter().sum(),
+            histogram,
+            diameter,
+            antipodes,
+        }
+    }
+}
+
+// The optimal distance from every state to the solved state like DistanceTable, but with one entry
+// per symmetry class, since mirroring or rotating a solution solves the mirrored or rotated state
+// in as many moves. A state is looked up by the rank of its canonical form, which costs applying
+// each of the 48 symmetries to it. The 66,321 classes take five bytes each, about 330 KB, where
+// DistanceTable takes a byte for each of the 3,149,280 states: about 9.5 times smaller. It always
+// covers every move.
+pub struct SymmetricDistanceTable {
+    // The canonical ranks in increasing order, and the distance of each.
+    ranks: Vec<u32>,
+    distances: Vec<u8>,
+}
+
+impl SymmetricDistanceTable {
+    // A breadth-first search like DistanceTable::build, but over canonical ranks only. Every move
+    // has its inverse among the moves, so the search doesn't have to turn them backwards. The
+    // SymmetryTable is only kept while building, where every state's canonical rank is needed.
+    pub fn build() -> SymmetricDistanceTable {
+        let symmetries = SymmetryTable::new();
+        let move_table = MoveTable::new(&Move::all());
+        let mut visited = vec![0u64; STATE_COUNT.div_ceil(64)];
+        let mut entries = vec![];
+
+        let solved = NormalizedSkewb::new().rank();
+        visited[solved / 64] |= 1 << (solved % 64);
+        entries.push((solved as u32, 0));
+        let mut frontier = vec![solved];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            let mut next = vec![];
+            for &rank in frontier.iter() {
+                for i in 0..move_table.moves().len() {
+                    let r = symmetries.canonical_rank(move_table.apply(rank, i));
+                    if visited[r / 64] & (1 << (r % 64)) == 0 {
+                        visited[r / 64] |= 1 << (r % 64);
+                        entries.push((r as u32, depth + 1));
+                        next.push(r);
+                    }
+                }
+            }
+            frontier = next;
+            depth += 1;
+        }
+
+        entries.sort();
+        SymmetricDistanceTable {
+            ranks: entries.iter().map(|&(rank, _)| rank).collect(),
+            distances: entries.iter().map(|&(_, d)| d).collect(),
+        }
+    }
+
+    // How many symmetry classes there are.
+    pub fn len(&self) -> usize { self.ranks.len() }
+
+    pub fn is_empty(&self) -> bool { self.ranks.is_empty() }
+
+    // The memory the entries take up.
+    pub fn size_in_bytes(&self) -> usize {
+        self.ranks.len() * mem::size_of::<u32>() + self.distances.len()
+    }
+
+    // The classes are of plain states, so a supercube state is UnsupportedSupercube rather than
+    // the distance of its pieces alone.
+    pub fn distance(&self, skewb: &NormalizedSkewb) -> Result<usize, SearchError> {
+        if skewb.center_orientations.is_some() {
+            return Err(SearchError::UnsupportedSupercube);
+        }
+        let rank = skewb.canonical().rank() as u32;
+        self.ranks
+            .binary_search(&rank)
+            .map(|i| self.distances[i] as usize)
+            .map_err(|_| SearchError::OutsideSubgroup)
+    }
+
+    // Five bytes per class: its canonical rank as a little-endian u32, then its distance.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len() * 5);
+        for (rank, &d) in self.ranks.iter().zip(self.distances.iter()) {
+            bytes.extend_from_slice(&rank.to_le_bytes());
+            bytes.push(d);
+        }
+        bytes
+    }
+
+    // Reads back what to_bytes wrote, or None if the bytes can't have come from it.
+    pub fn from_bytes(bytes: &[u8]) -> Option<SymmetricDistanceTable> {
+        if !bytes.len().is_multiple_of(5) {
+            return None;
+        }
+        let mut ranks = Vec::with_capacity(bytes.len() / 5);
+        let mut distances = Vec::with_capacity(bytes.len() / 5);
+        for entry in bytes.chunks(5) {
+            let rank = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            if rank as usize >= STATE_COUNT || ranks.last().is_some_and(|&last| last >= rank) {
+                return None;
+            }
+            ranks.push(rank);
+            distances.push(entry[4]);
+        }
+        Some(SymmetricDistanceTable { ranks, distances })
+    }
+}
+
+// How the states of the puzzle, or of the subgroup generated by some of its moves, are spread
+// out by optimal distance from solved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepthReport {
+    // How many states are at each distance.
+    pub histogram: Vec<u64>,
+    pub group_size: u64,
+    pub diameter: usize,
+    // The states at the largest distance.
+    pub antipodes: Vec<NormalizedSkewb>,
+}
+
+impl DepthReport {
+    // The report for the subgroup generated by turning only the given fixed corners.
+    pub fn for_corners(corners: &[Corner]) -> DepthReport {
+        let moves = MoveSet::new(corners, None).moves();
+        DistanceTable::build_parallel_with(&moves, default_threads()).report()
+    }
+}
+
+#[test]
+fn move_table_agrees_with_turning() {
+    let moves = Move::all();
+    let move_table = MoveTable::new(&moves);
+    let mut state = NormalizedSkewb::new();
+    for step in 0..50 {
+        let i = step * 5 % moves.len();
+        let rank = move_table.apply(state.rank(), i);
+        state.do_move(&moves[i]);
+        assert_eq!(state.rank(), rank);
+        assert_eq!(state, NormalizedSkewb::from_rank(rank));
+    }
+}
+
+#[test]
+fn parallel_build_matches() {
+    let table = DistanceTable::build();
+    for &threads in [1, 3, 8].iter() {
+        assert_eq!(
+            table.as_bytes(),
+            DistanceTable::build_parallel(threads).as_bytes()
+        );
+    }
+}
+
+#[test]
+fn whole_puzzle() {
+    let table = DistanceTable::build();
+    assert_eq!(11, table.diameter());
+    assert!(table.distances.iter().all(|&d| d != UNREACHED));
+    assert_eq!(Some(0), table.distance(&NormalizedSkewb::new()));
+
+    let mut skewb = NormalizedSkewb::new();
+    skewb.turn_lr((0, 0, 0));
+    skewb.turn_lr((1, 0, 1));
+    assert_eq!(Some(2), table.distance(&skewb));
+}
+
+#[test]
+fn depth_reports() {
+    use skewb::Direction;
+
+    let report = DistanceTable::build().report();
+    assert_eq!(
+        vec![1, 8, 48, 288, 1728, 10248, 59304, 315198, 1225483, 1455856, 81028, 90],
+        report.histogram
+    );
+    assert_eq!(STATE_COUNT as u64, report.group_size);
+    assert_eq!(11, report.diameter);
+    assert_eq!(90, report.antipodes.len());
+
+    let report = DepthReport::for_corners(&[(0, 0, 0)]);
+    assert_eq!(vec![1, 2], report.histogram);
+    assert_eq!(3, report.group_size);
+
+    let table = DistanceTable::build_with(&[
+        Move::new((0, 0, 0), Direction::LR),
+        Move::new((0, 0, 0), Direction::FB),
+        Move::new((1, 0, 1), Direction::LR),
+        Move::new((1, 0, 1), Direction::FB),
+    ]);
+    let report = table.report();
+    assert_eq!(report, DepthReport::for_corners(&[(0, 0, 0), (1, 0, 1)]));
+    assert_eq!(report.histogram.iter().sum::<u64>(), report.group_size);
+    for antipode in report.antipodes.iter() {
+        assert_eq!(Some(report.diameter), table.distance(antipode));
+    }
+}
+
+#[test]
+fn symmetric_table_agrees() {
+    let table = DistanceTable::build();
+    let symmetric = SymmetricDistanceTable::build();
+    assert_eq!(66321, symmetric.len());
+    assert_eq!(66321 * 5, symmetric.size_in_bytes());
+    assert!(symmetric.size_in_bytes() * 9 < table.as_bytes().len());
+    for rank in (0..STATE_COUNT).step_by(997) {
+        let state = NormalizedSkewb::from_rank(rank);
+        assert_eq!(table.distance(&state), symmetric.distance(&state).ok());
+    }
+    for antipode in table.report().antipodes.iter() {
+        assert_eq!(Ok(11), symmetric.distance(antipode));
+    }
+    assert_eq!(
+        Err(SearchError::UnsupportedSupercube),
+        symmetric.distance(&NormalizedSkewb::supercube())
+    );
+
+    let bytes = symmetric.to_bytes();
+    assert_eq!(symmetric.len() * 5, bytes.len());
+    let read = SymmetricDistanceTable::from_bytes(&bytes).unwrap();
+    assert_eq!(symmetric.ranks, read.ranks);
+    assert_eq!(symmetric.distances, read.distances);
+    assert!(SymmetricDistanceTable::from_bytes(&bytes[1..]).is_none());
+}
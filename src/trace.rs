@@ -0,0 +1,215 @@
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+
+use skewb::{
+    corner_name, Center, Color, Corner, Move, NormalizedSkewb, Skewb, CENTERS, FIXED_CORNERS,
+    FLOATING_CORNERS,
+};
+
+// The states a sequence of moves goes through, each with the move that reached it. The start
+// itself isn't included.
+pub struct Trace<'a, S> {
+    state: S,
+    moves: slice::Iter<'a, Move>,
+}
+
+// What a Trace needs to turn a puzzle.
+pub trait Traceable: Clone {
+    fn do_move(&mut self, move_: &Move);
+    // Where a piece is, or None if the puzzle has no such piece.
+    fn locate(&self, piece: Piece) -> Option<Location>;
+}
+
+impl<'a, S: Traceable> Iterator for Trace<'a, S> {
+    type Item = (Move, S);
+
+    fn next(&mut self) -> Option<(Move, S)> {
+        let move_ = *self.moves.next()?;
+        self.state.do_move(&move_);
+        Some((move_, self.state.clone()))
+    }
+}
+
+// A piece, named by its colours. A corner's are in the order of the axes they face on a solved
+// puzzle: U or D, then L or R, then B or F.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Piece {
+    Corner(Color, Color, Color),
+    Center(Color),
+}
+
+// A place a piece can be.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    Corner(Corner),
+    Center(Center),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Location::Corner(c) => write!(f, "{}", corner_name(c)),
+            Location::Center(c) => write!(f, "{:?}", c),
+        }
+    }
+}
+
+fn color(c: char) -> Result<Color, String> {
+    match c.to_ascii_uppercase() {
+        'B' => Ok(Color::B),
+        'G' => Ok(Color::G),
+        'R' => Ok(Color::R),
+        'O' => Ok(Color::O),
+        'Y' => Ok(Color::Y),
+        'W' => Ok(Color::W),
+        _ => Err(format!("{} is not a colour", c)),
+    }
+}
+
+// The axis a colour faces on a solved puzzle.
+fn axis(c: Color) -> usize {
+    match c {
+        Color::Y | Color::W => 0,
+        Color::O | Color::R => 1,
+        Color::G | Color::B => 2,
+    }
+}
+
+// The colours of a piece in any order, like "YOG" or "W".
+impl FromStr for Piece {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Piece, String> {
+        let mut colors = text
+            .chars()
+            .map(color)
+            .collect::<Result<Vec<Color>, String>>()?;
+        colors.sort_by_key(|&c| axis(c));
+        match colors[..] {
+            [c] => Ok(Piece::Center(c)),
+            [a, b, c] if (axis(a), axis(b), axis(c)) == (0, 1, 2) => Ok(Piece::Corner(a, b, c)),
+            _ => Err(format!("{} is not a piece", text)),
+        }
+    }
+}
+
+impl Traceable for Skewb {
+    fn do_move(&mut self, move_: &Move) { Skewb::do_move(self, move_) }
+
+    fn locate(&self, piece: Piece) -> Option<Location> {
+        match piece {
+            Piece::Corner(a, b, c) => FIXED_CORNERS
+                .iter()
+                .chain(FLOATING_CORNERS.iter())
+                .find(|&&corner| {
+                    let p = self.get_corner_piece(corner);
+                    let colors = [p.0, p.1, p.2];
+                    [a, b, c].iter().all(|x| colors.contains(x))
+                })
+                .map(|&corner| Location::Corner(corner)),
+            Piece::Center(c) => CENTERS
+                .iter()
+                .find(|&&center| self.get_center_piece(center) == c)
+                .map(|&center| Location::Center(center)),
+        }
+    }
+}
+
+impl Traceable for NormalizedSkewb {
+    fn do_move(&mut self, move_: &Move) { NormalizedSkewb::do_move(self, move_) }
+
+    fn locate(&self, piece: Piece) -> Option<Location> { self.clone().denormalize().locate(piece) }
+}
+
+// Where the piece is at the start and after each move, so that the nth entry is where it is after
+// move n.
+fn track<S: Traceable>(start: &S, moves: &[Move], piece: Piece) -> Vec<Option<Location>> {
+    let mut locations = vec![start.locate(piece)];
+    locations.extend(trace(start, moves).map(|(_, state)| state.locate(piece)));
+    locations
+}
+
+fn trace<'a, S: Traceable>(start: &S, moves: &'a [Move]) -> Trace<'a, S> {
+    Trace {
+        state: start.clone(),
+        moves: moves.iter(),
+    }
+}
+
+impl Skewb {
+    pub fn trace<'a>(&self, moves: &'a [Move]) -> Trace<'a, Skewb> { trace(self, moves) }
+    pub fn locate(&self, piece: Piece) -> Option<Location> { Traceable::locate(self, piece) }
+    pub fn track(&self, moves: &[Move], piece: Piece) -> Vec<Option<Location>> {
+        track(self, moves, piece)
+    }
+}
+
+impl NormalizedSkewb {
+    pub fn trace<'a>(&self, moves: &'a [Move]) -> Trace<'a, NormalizedSkewb> { trace(self, moves) }
+    pub fn locate(&self, piece: Piece) -> Option<Location> { Traceable::locate(self, piece) }
+    pub fn track(&self, moves: &[Move], piece: Piece) -> Vec<Option<Location>> {
+        track(self, moves, piece)
+    }
+}
+
+#[test]
+fn tracing() {
+    use solver::scramble_moves;
+
+    let moves = scramble_moves(2, 8);
+    let steps: Vec<(Move, NormalizedSkewb)> = NormalizedSkewb::new().trace(&moves).collect();
+    assert_eq!(moves.len(), steps.len());
+    let mut state = NormalizedSkewb::new();
+    for (move_, (traced_move, traced)) in moves.iter().zip(steps.iter()) {
+        state.do_move(move_);
+        assert_eq!(move_, traced_move);
+        assert_eq!(&state, traced);
+    }
+
+    let skewbs: Vec<(Move, Skewb)> = Skewb::new().trace(&moves).collect();
+    for ((_, skewb), (_, normalized)) in skewbs.into_iter().zip(steps) {
+        assert_eq!(normalized, skewb.normalize());
+    }
+}
+
+#[test]
+fn tracking_pieces() {
+    use skewb::Direction;
+
+    let piece: Piece = "YOG".parse().unwrap();
+    assert_eq!(Ok(piece), "goy".parse());
+    assert_eq!(Ok(Piece::Center(Color::W)), "W".parse());
+    assert!("YO".parse::<Piece>().is_err());
+    assert!("YOX".parse::<Piece>().is_err());
+    assert!("YWG".parse::<Piece>().is_err());
+
+    // UBL is fixed, so turning corners keeps its piece there.
+    let moves = [
+        Move::new((0, 0, 0), Direction::LR),
+        Move::new((0, 1, 1), Direction::FB),
+    ];
+    let solved = NormalizedSkewb::new();
+    assert_eq!(Some(Location::Corner((0, 0, 0))), solved.locate(piece));
+    let track = solved.track(&moves, piece);
+    assert_eq!(3, track.len());
+    assert!(track
+        .iter()
+        .all(|&l| l == Some(Location::Corner((0, 0, 0)))));
+    assert_eq!(track, Skewb::new().track(&moves, piece));
+    // But the floating corners around it move.
+    let floating = solved.track(&moves, "YOB".parse().unwrap());
+    assert_eq!(Some(Location::Corner((0, 0, 1))), floating[0]);
+    assert!(floating[1] != floating[0]);
+
+    let yellow = solved.track(&moves, Piece::Center(Color::Y));
+    assert_eq!(Some(Location::Center(Center::U)), yellow[0]);
+    assert!(yellow[1] != yellow[0]);
+    let state = solved.trace(&moves).next().unwrap().1;
+    assert_eq!("U", Location::Center(Center::U).to_string());
+    assert_eq!(yellow[1], state.locate(Piece::Center(Color::Y)));
+    assert_eq!(
+        None,
+        solved.locate(Piece::Corner(Color::Y, Color::W, Color::G))
+    );
+}
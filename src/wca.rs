@@ -0,0 +1,118 @@
+use notation::{from_wca, to_wca_from, WcaMove};
+use rotation::Frame;
+use skewb::{MoveSet, NormalizedSkewb};
+use solver::{SearchConfig, SearchError, Solver};
+
+// Finds the shortest solutions in WCA moves, which leave the puzzle solved but held any way round.
+// However the puzzle is held, each fixed-corner move can be made with exactly one WCA move and the
+// other way round, so shortest solutions are the same length in both and translating the other
+// solver's solution is optimal.
+pub struct WcaSolver<S> {
+    solver: S,
+}
+
+impl<S: Solver> WcaSolver<S> {
+    pub fn new(solver: S) -> WcaSolver<S> { WcaSolver { solver } }
+
+    // The solution for a puzzle in the start state, held with the corners of the normalized
+    // puzzle carried by the frame. Only searches with every move allowed.
+    pub fn solve(
+        &self,
+        start: &NormalizedSkewb,
+        frame: &Frame,
+        config: &SearchConfig,
+    ) -> Result<Vec<WcaMove>, SearchError> {
+        if config.moves != MoveSet::all() {
+            return Err(SearchError::UnsupportedMoves);
+        }
+        let moves = self.solver.solve(start, config)?;
+        Ok(to_wca_from(frame, &moves))
+    }
+
+    // The solution for a solved puzzle after the scramble.
+    pub fn solve_scramble(
+        &self,
+        scramble: &[WcaMove],
+        config: &SearchConfig,
+    ) -> Result<Vec<WcaMove>, SearchError> {
+        let (moves, frame) = from_wca(scramble);
+        self.solve(&NormalizedSkewb::after(&moves), &frame, config)
+    }
+}
+
+// Whether some WCA moves, at most depth of them, take the held puzzle to one of the solved ones.
+#[cfg(test)]
+fn solvable_within(held: &::skewb::Skewb, solved: &[::skewb::Skewb], depth: usize) -> bool {
+    if solved.contains(held) {
+        return true;
+    }
+    depth > 0
+        && WcaMove::all().iter().any(|wca| {
+            let mut next = held.clone();
+            next.do_move(&wca.to_move());
+            solvable_within(&next, solved, depth - 1)
+        })
+}
+
+#[test]
+fn optimal_in_wca_moves() {
+    use notation::to_wca;
+    use rotation::Rotation;
+    use skewb::Skewb;
+    use solver::{scramble_moves, IdaStar};
+
+    // Every way round a solved puzzle can be held.
+    let mut solved = vec![Skewb::new()];
+    let mut i = 0;
+    while i < solved.len() {
+        for &rotation in Rotation::all().iter() {
+            let mut rotated = solved[i].clone();
+            rotated.rotate(rotation);
+            if !solved.contains(&rotated) {
+                solved.push(rotated);
+            }
+        }
+        i += 1;
+    }
+    assert_eq!(24, solved.len());
+
+    let solver = WcaSolver::new(IdaStar::new());
+    let config = SearchConfig::default();
+    for seed in 0..10 {
+        let scramble = to_wca(&scramble_moves(seed, 4));
+        let mut held = Skewb::new();
+        for wca in scramble.iter() {
+            held.do_move(&wca.to_move());
+        }
+
+        let solution = solver.solve_scramble(&scramble, &config).unwrap();
+        let mut solved_held = held.clone();
+        for wca in solution.iter() {
+            solved_held.do_move(&wca.to_move());
+        }
+        assert!(solved.contains(&solved_held));
+        if !solution.is_empty() {
+            assert!(!solvable_within(&held, &solved, solution.len() - 1));
+        }
+    }
+
+    let config = SearchConfig {
+        moves: MoveSet::new(&[(0, 0, 0)], None),
+        ..SearchConfig::default()
+    };
+    assert_eq!(
+        Err(SearchError::UnsupportedMoves),
+        solver.solve_scramble(&[], &config)
+    );
+
+    // Every move, however the corners are listed.
+    let config = SearchConfig {
+        moves: MoveSet::new(
+            &[(1, 1, 0), (0, 0, 0), (1, 0, 1), (0, 1, 1), (0, 0, 0)],
+            None,
+        ),
+        ..SearchConfig::default()
+    };
+    assert_eq!(MoveSet::all(), config.moves);
+    assert_eq!(Ok(vec![]), solver.solve_scramble(&[], &config));
+}